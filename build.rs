@@ -0,0 +1,65 @@
+use std::{
+    env,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Every file of `dir`, recursively
+fn files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    let mut found = vec![];
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+        if path.is_dir() {
+            found.extend(files(&path));
+        } else {
+            found.push(path);
+        }
+    }
+    found
+}
+
+/// Lists the files of `assets/` for the `embedded_assets` feature
+fn main() {
+    println!("cargo:rerun-if-changed=assets");
+
+    // Nothing is embedded otherwise
+    if env::var_os("CARGO_FEATURE_EMBEDDED_ASSETS").is_none()
+        || env::var_os("CARGO_FEATURE_DEV").is_some()
+    {
+        return;
+    }
+
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("cargo sets the manifest dir");
+    let assets_dir = Path::new(&manifest_dir).join("assets");
+
+    let mut assets = files(&assets_dir);
+    assets.sort();
+
+    let mut list = String::from("&[\n");
+    for path in &assets {
+        let name = path
+            .strip_prefix(&assets_dir)
+            .expect("assets are found in the assets dir");
+        // Asset paths always use forward slashes
+        let name = name
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        writeln!(
+            list,
+            "    ({name:?}, include_bytes!({:?})),",
+            path.display().to_string()
+        )
+        .expect("writing to a string can't fail");
+    }
+    list.push_str("]\n");
+
+    let out_dir = env::var("OUT_DIR").expect("cargo sets the out dir");
+    fs::write(Path::new(&out_dir).join("embedded_assets.rs"), list)
+        .expect("the embedded asset list can be written in the out dir");
+}
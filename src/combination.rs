@@ -20,6 +20,29 @@ pub enum Combination {
 }
 
 impl Combination {
+    /// One combination of each kind, 421 first
+    const EXAMPLES: &'static [Self; 8] = &[
+        Combination::FourTwoOne(3),
+        Combination::Strike(6),
+        Combination::Ace(6),
+        Combination::Straight(6, 3),
+        Combination::HighestRoll(Vec::new()),
+        Combination::HighRoll(Vec::new()),
+        Combination::LowRoll(Vec::new()),
+        Combination::Any(Vec::new()),
+    ];
+
+    /// Names of the kinds of combination, 421 first
+    pub const KINDS: [&'static str; 8] = {
+        let mut kinds = [""; 8];
+        let mut i = 0;
+        while i < kinds.len() {
+            kinds[i] = Self::EXAMPLES[i].kind();
+            i += 1;
+        }
+        kinds
+    };
+
     pub fn get(mut results: Vec<DiceResult>) -> Self {
         assert!(results.len() >= MIN_NB_DICES);
 
@@ -94,6 +117,75 @@ impl Combination {
         }
     }
 
+    /// Name of the combination, without its value
+    pub fn name(&self) -> String {
+        match self {
+            Combination::FourTwoOne(_) => "Four-Two-One!".to_string(),
+            Combination::Ace(dice) => format!("{dice} Ace"),
+            Combination::Strike(dice) => format!("{dice} Strike"),
+            Combination::Straight(dice, len) => format!("Straight {}", {
+                (0..*len)
+                    .rev()
+                    .map(|i| (dice - i as u8).to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            }),
+            Combination::HighestRoll(dices) => format!(
+                "Highest Roll {}",
+                dices.iter().map(ToString::to_string).collect::<String>()
+            ),
+            Combination::HighRoll(dices) => format!(
+                "High Roll {}",
+                dices.iter().map(ToString::to_string).collect::<String>()
+            ),
+            Combination::LowRoll(dices) => format!(
+                "Low Roll {}",
+                dices.iter().map(ToString::to_string).collect::<String>()
+            ),
+            Combination::Any(dices) => dices.iter().map(ToString::to_string).collect::<String>(),
+        }
+    }
+
+    /// Name of the kind of combination, whatever its dices
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Combination::FourTwoOne(_) => "Four-Two-One",
+            Combination::Strike(_) => "Strike",
+            Combination::Ace(_) => "Ace",
+            Combination::Straight(..) => "Straight",
+            Combination::HighestRoll(_) => "Highest Roll",
+            Combination::HighRoll(_) => "High Roll",
+            Combination::LowRoll(_) => "Low Roll",
+            Combination::Any(_) => "Any",
+        }
+    }
+
+    /// Indices of the `results` making up this combination, `results` being the ones it was made from
+    pub fn contributors(&self, results: &[DiceResult]) -> Vec<usize> {
+        // One dice of each value
+        let pick = |values: &[DiceResult]| {
+            values
+                .iter()
+                .filter_map(|value| results.iter().position(|r| r == value))
+                .collect()
+        };
+
+        match self {
+            Combination::Any(_) => vec![],
+            Combination::Straight(highest, len) => pick(
+                &(0..*len as DiceResult)
+                    .map(|i| highest - i)
+                    .collect::<Vec<_>>(),
+            ),
+            Combination::FourTwoOne(_) => pick(&[4, 2, 1]),
+            Combination::LowRoll(_)
+            | Combination::HighRoll(_)
+            | Combination::HighestRoll(_)
+            | Combination::Strike(_)
+            | Combination::Ace(_) => (0..results.len()).collect(),
+        }
+    }
+
     unsafe fn discriminant(&self) -> u8 {
         *std::ptr::from_ref::<Self>(self).cast::<u8>()
     }
@@ -140,37 +232,63 @@ impl Ord for Combination {
 
 impl fmt::Display for Combination {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}  [{}¤]",
-            match self {
-                Combination::FourTwoOne(_) => "Four-Two-One!".to_string(),
-                Combination::Ace(dice) => format!("{dice} Ace"),
-                Combination::Strike(dice) => format!("{dice} Strike"),
-                Combination::Straight(dice, len) => format!("Straight {}", {
-                    (0..*len)
-                        .rev()
-                        .map(|i| (dice - i as u8).to_string())
-                        .collect::<Vec<String>>()
-                        .join(",")
-                }),
-                Combination::HighestRoll(dices) => format!(
-                    "Highest Roll {}",
-                    dices.iter().map(ToString::to_string).collect::<String>()
-                ),
-                Combination::HighRoll(dices) => format!(
-                    "High Roll {}",
-                    dices.iter().map(ToString::to_string).collect::<String>()
-                ),
-                Combination::LowRoll(dices) => format!(
-                    "Low Roll {}",
-                    dices.iter().map(ToString::to_string).collect::<String>()
-                ),
-                Combination::Any(dices) => {
-                    dices.iter().map(ToString::to_string).collect::<String>()
-                }
-            },
-            self.score()
-        )
+        write!(f, "{}  [{}¤]", self.name(), self.score())
+    }
+}
+
+/// The final dices of a side, and the combination they make
+pub struct Roll {
+    pub results: Vec<DiceResult>,
+    pub combination: Combination,
+}
+
+impl Roll {
+    pub fn new(results: Vec<DiceResult>) -> Self {
+        Self {
+            combination: Combination::get(results.clone()),
+            results,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contributors(results: &[DiceResult]) -> Vec<usize> {
+        Combination::get(results.to_vec()).contributors(results)
+    }
+
+    #[test]
+    fn triples_and_aces_use_every_dice() {
+        assert_eq!(contributors(&[5, 5, 5]), [0, 1, 2]);
+        assert_eq!(contributors(&[1, 1, 1]), [0, 1, 2]);
+        // The pair of 1 and the ace
+        assert_eq!(contributors(&[1, 6, 1]), [0, 1, 2]);
+    }
+
+    #[test]
+    fn pairs_count_only_in_rolls() {
+        assert_eq!(contributors(&[5, 4, 5]), [0, 1, 2]);
+        assert_eq!(contributors(&[2, 1, 2]), [0, 1, 2]);
+        assert!(contributors(&[3, 6, 3]).is_empty());
+    }
+
+    #[test]
+    fn four_two_one_uses_one_dice_per_value() {
+        assert_eq!(contributors(&[4, 2, 1]), [0, 1, 2]);
+        assert_eq!(contributors(&[1, 4, 2]), [1, 2, 0]);
+    }
+
+    #[test]
+    fn straights_are_listed_from_their_highest_dice() {
+        assert_eq!(contributors(&[6, 4, 5]), [0, 2, 1]);
+        assert_eq!(contributors(&[3, 1, 2]), [0, 2, 1]);
+    }
+
+    #[test]
+    fn any_has_no_contributors() {
+        assert!(contributors(&[6, 3, 5]).is_empty());
+        assert!(contributors(&[1, 5, 3]).is_empty());
     }
 }
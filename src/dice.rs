@@ -1,24 +1,66 @@
 use avian3d::prelude::*;
-use bevy::ecs::world::Command;
-use bevy::prelude::*;
+use bevy::ecs::{query::QueryData, world::Command};
+use bevy::{color::palettes::css::YELLOW, gltf::Gltf, prelude::*};
 use rand::prelude::*;
 
-use crate::combination::{Combination, DiceResult};
-use crate::game::{CanSkipTurn, GameState, RetriesLeft};
+use crate::combination::DiceResult;
+use crate::game::{
+    CanSkipTurn, GameState, RetriesLeft, Side, SuddenDeathDice, SuddenDeathOver, TurnOver,
+};
+use crate::loading::asset_path;
+use crate::npc::{NPCDicesSettled, NPCThrow};
 use crate::player::PlayerDice;
-use crate::table::TablePart;
-use crate::ui::DisplayScore;
+use crate::settings::{CockedDiceRule, GameplaySettings, StuckDiceRecovery};
+use crate::table::{TablePart, TRAY_RADIUS, TRAY_RING_HEIGHT, TRAY_THICKNESS};
 
+pub const DICE_ASSET: &str = "dice.glb";
 pub const NB_DICES: usize = 3;
 pub const MIN_NB_DICES: usize = 2;
-const MAX_ANGULAR_SPEED: f32 = 10.0;
-const MIN_FORCE: f32 = 50.0;
-const MAX_FORCE: f32 = 100.0;
+pub const MAX_ANGULAR_SPEED: f32 = 10.0;
+/// Time for a dice thrown with the lowest power to reach its target, in seconds
+const SLOWEST_THROW: f32 = 1.2;
+/// Time for a dice thrown with the highest power to reach its target, in seconds
+const FASTEST_THROW: f32 = 0.6;
 const MIN_MOVEMENT: f32 = 0.3;
+const COCKED_ALIGNMENT: f32 = 0.9;
+const COCKED_REROLL_SPEED: f32 = 6.0;
+const COCKED_REROLL_DELAY: f32 = 1.0;
+pub const LINEAR_DAMPING: f32 = 0.5;
+pub const PHYSICS_HZ: f64 = 60.0;
+const PREDICTION_STEP: f32 = 1.0 / PHYSICS_HZ as f32;
+const PREDICTION_MAX_STEPS: usize = 600;
+const SETTLE_TIMEOUT: f32 = 8.0;
+const NUDGE_SPEED: f32 = 3.0;
+const DENSITY: f32 = 5.0;
+/// Brightness of the tint of a dice making the combination on display
+const CONTRIBUTING_GLOW: f32 = 4.0;
+
+/// Pips of each face on a 3x3 grid going from -1 to 1
+pub const PIPS: [&[(f32, f32)]; 6] = [
+    &[(0.0, 0.0)],
+    &[(-1.0, -1.0), (1.0, 1.0)],
+    &[(-1.0, -1.0), (0.0, 0.0), (1.0, 1.0)],
+    &[(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)],
+    &[
+        (-1.0, -1.0),
+        (-1.0, 1.0),
+        (0.0, 0.0),
+        (1.0, -1.0),
+        (1.0, 1.0),
+    ],
+    &[
+        (-1.0, -1.0),
+        (-1.0, 0.0),
+        (-1.0, 1.0),
+        (1.0, -1.0),
+        (1.0, 0.0),
+        (1.0, 1.0),
+    ],
+];
 
 #[derive(Component)]
 pub struct Dice {
-    i: usize,
+    pub i: usize,
     pub size: f32,
     face_normals: Vec<Vec3>,
     pub asset_name: String,
@@ -37,10 +79,47 @@ impl Dice {
                 Vec3::new(-1.0, 0.0, 0.0), // Left face
                 Vec3::new(0.0, -1.0, 0.0), // Bottom face
             ],
-            asset_name: "dice.glb".into(),
+            asset_name: DICE_ASSET.into(),
         }
     }
 
+    /// Face pointing up, and how much it is aligned with the world up vector (1 = flat)
+    pub fn read(&self, rotation: Quat) -> (DiceResult, f32) {
+        // Determine which face is most aligned with the world up vector
+        let mut max_dot = -1.0;
+        let mut result = 0;
+
+        for (i, normal) in self.face_normals.iter().enumerate() {
+            // Rotate the face normal to the current orientation
+            let transformed_normal = rotation * *normal;
+
+            // Compare it with the world up vector
+            let dot_product = transformed_normal.dot(Vec3::Y);
+
+            // Check if this face is more aligned with the up direction
+            if dot_product > max_dot {
+                max_dot = dot_product;
+                result = i + 1;
+            }
+        }
+
+        (result as DiceResult, max_dot)
+    }
+
+    /// Position of every pip, relative to the center of the dice
+    fn pip_positions(&self) -> Vec<Vec3> {
+        let mut positions = Vec::new();
+
+        for (normal, pips) in self.face_normals.iter().zip(PIPS) {
+            let (u, v) = normal.any_orthonormal_pair();
+            for (x, y) in pips {
+                positions.push((*normal * 0.5 + (u * *x + v * *y) * 0.25) * self.size);
+            }
+        }
+
+        positions
+    }
+
     pub fn in_hand_transform(&self, thrower_position: Vec3) -> Transform {
         Transform::from_translation(
             thrower_position
@@ -54,11 +133,89 @@ impl Dice {
 }
 
 #[derive(Event)]
-pub struct RollDice(pub Vec3);
+pub enum RollDice {
+    /// Throw towards a point on the table, `power` going from 0 to 1
+    Aim { target: Vec3, power: f32 },
+    /// Throw with a given velocity and spin, drawn by the player
+    Launch { velocity: Vec3, spin: Vec3 },
+}
+
+/// Velocity of a dice thrown from `from` to reach `target`, in meters per second.
+/// Solves its flight under `gravity` and the linear damping, which lasts less with more `power`
+pub fn aim_velocity(from: Vec3, target: Vec3, power: f32, gravity: Vec3) -> Vec3 {
+    let flight_time = SLOWEST_THROW.lerp(FASTEST_THROW, power);
+    let damping = LINEAR_DAMPING;
+
+    // The position after t is `from + terminal * t + (velocity - terminal) * (1 - e^(-damping * t)) / damping`
+    let terminal = gravity / damping;
+    let decay = (1.0 - (-damping * flight_time).exp()) / damping;
+    terminal + (target - from - terminal * flight_time) / decay
+}
 
 #[derive(Component)]
 pub struct InHand;
 
+/// A dice resting tilted, against the tray ring or another dice, since the given elapsed time
+#[derive(Component)]
+pub struct Cocked(f32);
+
+/// Time left for a thrown dice to settle before it gets some help
+#[derive(Component)]
+pub struct SettleTimer(Timer);
+
+impl Default for SettleTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(SETTLE_TIMEOUT, TimerMode::Once))
+    }
+}
+
+/// The tint cube around a dice, with its normal color
+#[derive(Component)]
+pub struct DiceTint(pub Color);
+
+/// Value read on a dice resting on the table
+#[derive(Component, PartialEq, Eq)]
+pub struct SettledFace(pub DiceResult);
+
+/// A dice making the combination on display, glowing through its tint
+#[derive(Component)]
+pub struct Contributing;
+
+fn random_spin() -> Vec3 {
+    let mut rng = thread_rng();
+
+    Vec3::new(
+        rng.gen_range(-MAX_ANGULAR_SPEED..MAX_ANGULAR_SPEED),
+        rng.gen_range(-MAX_ANGULAR_SPEED..MAX_ANGULAR_SPEED),
+        rng.gen_range(-MAX_ANGULAR_SPEED..MAX_ANGULAR_SPEED),
+    )
+}
+
+/// Points along the flight of a dice launched at `velocity`, until it gets down to `landing_height`
+pub fn predict_trajectory(
+    from: Vec3,
+    mut velocity: Vec3,
+    gravity: Vec3,
+    landing_height: f32,
+) -> Vec<Vec3> {
+    let mut position = from;
+    let mut points = vec![position];
+
+    for _ in 0..PREDICTION_MAX_STEPS {
+        // Same integration as the physics engine, without collisions
+        velocity += gravity * PREDICTION_STEP;
+        velocity /= 1.0 + PREDICTION_STEP * LINEAR_DAMPING;
+        position += velocity * PREDICTION_STEP;
+        points.push(position);
+
+        if position.y <= landing_height {
+            break;
+        }
+    }
+
+    points
+}
+
 #[derive(Bundle)]
 pub struct InHandBundle {
     in_hand: InHand,
@@ -84,12 +241,19 @@ impl Command for NewDiceCommand {
     fn apply(self, world: &mut World) {
         let dice = Dice::new_6(self.i);
 
+        // The model is loaded by the loading state, it may have failed
         let asset_server = world.get_resource::<AssetServer>().unwrap();
-        let scene_dice =
-            asset_server.load(GltfAssetLabel::Scene(0).from_asset(dice.asset_name.clone()));
+        let gltf = asset_server.load::<Gltf>(asset_path(dice.asset_name.clone()));
+        let scene_dice = world
+            .get_resource::<Assets<Gltf>>()
+            .unwrap()
+            .get(&gltf)
+            .and_then(|gltf| gltf.scenes.first().cloned());
 
         let mut meshes = world.get_resource_mut::<Assets<Mesh>>().unwrap();
         let tint_mesh = meshes.add(Cuboid::from_length(dice.size * 1.1));
+        let body_mesh = meshes.add(Cuboid::from_length(dice.size));
+        let pip_mesh = meshes.add(Sphere::new(dice.size * 0.08));
 
         let mut materials = world
             .get_resource_mut::<Assets<StandardMaterial>>()
@@ -97,26 +261,62 @@ impl Command for NewDiceCommand {
         let mut tint_color = self.tint_color;
         tint_color.set_alpha(0.3);
         let tint_material = materials.add(tint_color);
-
-        world
-            .entity_mut(self.entity)
-            .insert((
-                Name::new(format!("dice_{}", self.i)),
-                RigidBody::Dynamic,
+        let body_material = materials.add(Color::WHITE);
+        let pip_material = materials.add(Color::BLACK);
+
+        let pip_positions = dice.pip_positions();
+        let size = dice.size;
+
+        let mut entity = world.entity_mut(self.entity);
+        entity.insert((
+            Name::new(format!("dice_{}", self.i)),
+            RigidBody::Dynamic,
+            LinearDamping(LINEAR_DAMPING),
+            dice,
+            InHandBundle::default(),
+        ));
+
+        if let Some(scene_dice) = scene_dice {
+            entity.insert((
                 ColliderConstructorHierarchy::new(ColliderConstructor::ConvexHullFromMesh)
                     .without_constructor_for_name("tint")
-                    .with_default_density(5.0),
-                LinearDamping(0.5),
+                    .with_default_density(DENSITY),
                 SceneBundle {
                     scene: scene_dice,
                     ..default()
                 },
-                dice,
-                InHandBundle::default(),
-            ))
+            ));
+        } else {
+            // Plain cube with pips, the collider on a child like the model would have it
+            entity.insert(SpatialBundle::default()).with_children(|c| {
+                c.spawn((
+                    Name::new("body"),
+                    Collider::cuboid(size, size, size),
+                    ColliderDensity(DENSITY),
+                    PbrBundle {
+                        mesh: body_mesh,
+                        material: body_material,
+                        ..default()
+                    },
+                ))
+                .with_children(|c| {
+                    for position in pip_positions {
+                        c.spawn(PbrBundle {
+                            mesh: pip_mesh.clone(),
+                            material: pip_material.clone(),
+                            transform: Transform::from_translation(position),
+                            ..default()
+                        });
+                    }
+                });
+            });
+        }
+
+        entity
             .with_children(|c| {
                 c.spawn((
                     Name::new("tint"),
+                    DiceTint(tint_color),
                     PbrBundle {
                         material: tint_material,
                         mesh: tint_mesh,
@@ -132,26 +332,40 @@ pub fn on_roll_dice(
     trigger: Trigger<RollDice>,
     mut commands: Commands,
     mut q_dices: Query<(&Transform, &mut AngularVelocity, &mut LinearVelocity), With<Dice>>,
-    time: Res<Time>,
+    gravity: Res<Gravity>,
 ) {
     let entity = trigger.entity();
     let (transform, mut angular_velocity, mut linear_velocity) = q_dices.get_mut(entity).unwrap();
 
     // Release the dice from the hand
-    commands.entity(entity).remove::<InHandBundle>();
+    commands
+        .entity(entity)
+        .remove::<(InHandBundle, Cocked)>()
+        .insert(SettleTimer::default());
 
     // Roll the dice
-    let mut rng = thread_rng();
-
-    let trajectory = trigger.event().0 - transform.translation;
-    let force = trajectory * rng.gen_range(MIN_FORCE..MAX_FORCE);
-    linear_velocity.0 = force * time.delta_seconds();
+    match *trigger.event() {
+        RollDice::Aim { target, power } => {
+            linear_velocity.0 = aim_velocity(transform.translation, target, power, gravity.0);
+            angular_velocity.0 = random_spin();
+        }
+        RollDice::Launch { velocity, spin } => {
+            linear_velocity.0 = velocity;
+            angular_velocity.0 = spin;
+        }
+    }
+}
 
-    angular_velocity.0 = Vec3::new(
-        rng.gen_range(-MAX_ANGULAR_SPEED..MAX_ANGULAR_SPEED),
-        rng.gen_range(-MAX_ANGULAR_SPEED..MAX_ANGULAR_SPEED),
-        rng.gen_range(-MAX_ANGULAR_SPEED..MAX_ANGULAR_SPEED),
-    );
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct DiceOnTable {
+    entity: Entity,
+    dice: &'static Dice,
+    transform: &'static Transform,
+    angular_velocity: &'static AngularVelocity,
+    linear_velocity: &'static LinearVelocity,
+    cocked: Option<&'static Cocked>,
+    settle_timer: Option<&'static mut SettleTimer>,
 }
 
 pub fn analyze_dices(
@@ -159,99 +373,385 @@ pub fn analyze_dices(
     retries: Res<RetriesLeft>,
     collisions: Res<Collisions>,
     q_table_parts: Query<Entity, With<TablePart>>,
-    q_player_dices_on_table: Query<
-        (Entity, &Dice, &Transform, &AngularVelocity, &LinearVelocity),
-        (Without<InHand>, With<PlayerDice>),
-    >,
-    q_npc_dices_on_table: Query<
-        (Entity, &Dice, &Transform, &AngularVelocity, &LinearVelocity),
-        (Without<InHand>, Without<PlayerDice>),
-    >,
+    mut q_player_dices_on_table: Query<DiceOnTable, (Without<InHand>, With<PlayerDice>)>,
+    mut q_npc_dices_on_table: Query<DiceOnTable, (Without<InHand>, Without<PlayerDice>)>,
     q_children: Query<&Children>,
+    q_sudden_death: Query<(), With<SuddenDeathDice>>,
+    q_settled_faces: Query<(Entity, Option<&SettledFace>), With<Dice>>,
     state: Res<State<GameState>>,
-    mut next_state: ResMut<NextState<GameState>>,
     mut can_skip_turn: ResMut<CanSkipTurn>,
+    gameplay: Res<GameplaySettings>,
+    time: Res<Time>,
 ) {
-    let read_dice = |dice_components: (
-        Entity,
-        &Dice,
-        &Transform,
-        &AngularVelocity,
-        &LinearVelocity,
-    )|
-     -> Option<DiceResult> {
-        let (entity, dice, transform, angular_velocity, linear_velocity) = dice_components;
-
-        if linear_velocity.0.length() < MIN_MOVEMENT
-            && angular_velocity.0.length() < MIN_MOVEMENT
+    let mut read_dice = |dice: DiceOnTableItem, is_player: bool| -> Option<DiceResult> {
+        let entity = dice.entity;
+
+        if dice.linear_velocity.0.length() < MIN_MOVEMENT
+            && dice.angular_velocity.0.length() < MIN_MOVEMENT
             && q_table_parts.iter().any(|table_part| {
                 q_children
                     .iter_descendants(entity)
                     .any(|c| collisions.contains(c, table_part))
             })
         {
-            // Determine which face is most aligned with the world up vector
-            let mut max_dot = -1.0;
-            let mut result = 0;
+            let (result, alignment) = dice.dice.read(dice.transform.rotation);
 
-            for (i, normal) in dice.face_normals.iter().enumerate() {
-                // Rotate the face normal to the current orientation
-                let transformed_normal = transform.rotation * *normal;
-
-                // Compare it with the world up vector
-                let dot_product = transformed_normal.dot(Vec3::Y);
+            if alignment >= COCKED_ALIGNMENT {
+                // Knocked flat after being cocked
+                if dice.cocked.is_some() {
+                    commands.entity(entity).remove::<Cocked>();
+                }
 
-                // Check if this face is more aligned with the up direction
-                if dot_product > max_dot {
-                    max_dot = dot_product;
-                    result = i + 1;
+                // Settled, the timer only runs while the dice is moving
+                if let Some(mut settle_timer) = dice.settle_timer {
+                    settle_timer.0.reset();
                 }
+                return Some(result);
             }
 
-            return Some(result as DiceResult);
+            let Some(Cocked(since)) = dice.cocked else {
+                commands
+                    .entity(entity)
+                    .insert(Cocked(time.elapsed_seconds()));
+                return None;
+            };
+
+            return match gameplay.cocked_dice {
+                // Leave some time to see what happened
+                CockedDiceRule::Rethrow if time.elapsed_seconds() - since < COCKED_REROLL_DELAY => {
+                    None
+                }
+                CockedDiceRule::Rethrow => {
+                    commands.trigger_targets(
+                        RollDice::Launch {
+                            velocity: Vec3::Y * COCKED_REROLL_SPEED,
+                            spin: random_spin(),
+                        },
+                        entity,
+                    );
+                    None
+                }
+                CockedDiceRule::CountsAsLowest => {
+                    if let Some(mut settle_timer) = dice.settle_timer {
+                        settle_timer.0.reset();
+                    }
+                    Some(1)
+                }
+                CockedDiceRule::PickUp => {
+                    // The player has to pick it up, the NPC does it right away
+                    if is_player {
+                        if let Some(mut settle_timer) = dice.settle_timer {
+                            settle_timer.0.reset();
+                        }
+                    } else {
+                        commands.trigger_targets(NPCThrow, entity);
+                    }
+                    None
+                }
+            };
         }
 
         None
     };
 
     let results_npc = q_npc_dices_on_table
-        .iter()
-        .filter_map(read_dice)
+        .iter_mut()
+        .filter_map(|dice| {
+            let entity = dice.entity;
+            read_dice(dice, false).map(|result| (entity, result))
+        })
         .collect::<Vec<_>>();
 
     let results_player = q_player_dices_on_table
-        .iter()
-        .filter_map(read_dice)
+        .iter_mut()
+        .filter_map(|dice| {
+            let entity = dice.entity;
+            read_dice(dice, true).map(|result| (entity, result))
+        })
         .collect::<Vec<_>>();
 
     can_skip_turn.0 = results_player.len() == NB_DICES;
 
+    // Keep the value of each dice for as long as it rests on the table
+    for (entity, settled_face) in &q_settled_faces {
+        let face = results_npc
+            .iter()
+            .chain(&results_player)
+            .find(|(e, _)| *e == entity)
+            .map(|(_, result)| SettledFace(*result));
+
+        match face {
+            Some(face) if settled_face != Some(&face) => {
+                commands.entity(entity).insert(face);
+            }
+            None if settled_face.is_some() => {
+                commands.entity(entity).remove::<SettledFace>();
+            }
+            _ => {}
+        }
+    }
+
     match state.get() {
         GameState::NPCRolling => {
-            // If NPC finished rolling, proceed to player's turn
+            // Let the NPC decide whether to throw again, or proceed to player's turn
             if results_npc.len() == NB_DICES {
-                commands.trigger(DisplayScore::npc(Combination::get(results_npc)));
-                next_state.set(GameState::PlayerRolling);
+                commands.trigger(NPCDicesSettled(results_npc));
             }
         }
 
         GameState::PlayerRolling => {
-            // If player finished rolling (= out of retries, player dices are not moving) and NPC dices are not moving
-            if retries.0 == 0 && results_npc.len() == NB_DICES && results_player.len() == NB_DICES {
-                // calculate the score
+            // If player finished rolling (= out of retries, player dices are not moving)
+            if retries.0 == 0 && results_player.len() == NB_DICES {
+                let (dices, results) = results_player.into_iter().unzip();
+                commands.trigger(TurnOver {
+                    side: Side::Player,
+                    results,
+                    dices,
+                });
+            }
+        }
+
+        GameState::SuddenDeath => {
+            let sudden_death_result = |results: &[(Entity, DiceResult)]| {
+                results
+                    .iter()
+                    .find(|(entity, _)| q_sudden_death.contains(*entity))
+                    .map(|(_, result)| *result)
+            };
+
+            if let (Some(player), Some(npc)) = (
+                sudden_death_result(&results_player),
+                sudden_death_result(&results_npc),
+            ) {
+                commands.trigger(SuddenDeathOver { player, npc });
+            }
+        }
 
-                let player = Combination::get(results_player);
-                let npc = Combination::get(results_npc);
+        _ => {}
+    }
+}
 
-                let wins = player >= npc;
+/// Forces a resolution for dices that did not settle in time, so that the round can end
+pub fn recover_stuck_dices(
+    mut commands: Commands,
+    mut q_dices: Query<
+        (
+            Entity,
+            &Name,
+            &Dice,
+            &mut Transform,
+            &mut LinearVelocity,
+            &mut AngularVelocity,
+            &mut SettleTimer,
+        ),
+        Without<InHand>,
+    >,
+    gameplay: Res<GameplaySettings>,
+    time: Res<Time>,
+) {
+    let mut rng = thread_rng();
 
-                commands.trigger(DisplayScore::player(npc, player, wins));
+    for (
+        entity,
+        name,
+        dice,
+        mut transform,
+        mut linear_velocity,
+        mut angular_velocity,
+        mut settle_timer,
+    ) in &mut q_dices
+    {
+        if !settle_timer.0.tick(time.delta()).just_finished() {
+            continue;
+        }
 
-                //TODO: go to shop
-                next_state.set(GameState::NPCRolling);
+        warn!(
+            "{name} did not settle after {SETTLE_TIMEOUT}s, recovering with: {}",
+            gameplay.stuck_dice.label()
+        );
+
+        settle_timer.0.reset();
+
+        match gameplay.stuck_dice {
+            StuckDiceRecovery::Nudge => {
+                linear_velocity.0 += Vec3::new(
+                    rng.gen_range(-NUDGE_SPEED..NUDGE_SPEED),
+                    NUDGE_SPEED,
+                    rng.gen_range(-NUDGE_SPEED..NUDGE_SPEED),
+                );
+                angular_velocity.0 += random_spin() * 0.2;
+            }
+            StuckDiceRecovery::Rethrow => {
+                transform.translation = Vec3::new(
+                    rng.gen_range(-1.0..1.0),
+                    TRAY_RING_HEIGHT,
+                    rng.gen_range(-1.0..1.0),
+                );
+                commands.trigger_targets(
+                    RollDice::Launch {
+                        velocity: Vec3::ZERO,
+                        spin: random_spin(),
+                    },
+                    entity,
+                );
+            }
+            StuckDiceRecovery::SnapToFace => {
+                // Rotate the face pointing up the most so that it points straight up
+                let (result, _) = dice.read(transform.rotation);
+                let up = transform.rotation * dice.face_normals[result as usize - 1];
+                transform.rotation = Quat::from_rotation_arc(up, Vec3::Y) * transform.rotation;
+
+                let position = transform
+                    .translation
+                    .xz()
+                    .clamp_length_max(TRAY_RADIUS - dice.size);
+                transform.translation =
+                    Vec3::new(position.x, (TRAY_THICKNESS + dice.size) / 2.0, position.y);
+
+                linear_velocity.0 = Vec3::ZERO;
+                angular_velocity.0 = Vec3::ZERO;
+                commands.entity(entity).remove::<Cocked>();
             }
         }
+    }
+}
 
-        _ => {}
+pub fn update_dice_tints(
+    q_tints: Query<(&Parent, &Handle<StandardMaterial>, &DiceTint)>,
+    q_dices: Query<(Has<Cocked>, Has<Contributing>), With<Dice>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (parent, material, tint) in &q_tints {
+        let Ok((cocked, contributing)) = q_dices.get(parent.get()) else {
+            continue;
+        };
+
+        let (color, emissive) = if cocked {
+            (Color::from(YELLOW).with_alpha(0.6), LinearRgba::BLACK)
+        } else if contributing {
+            (
+                tint.0.with_alpha(0.5),
+                LinearRgba::from(tint.0) * CONTRIBUTING_GLOW,
+            )
+        } else {
+            (tint.0, LinearRgba::BLACK)
+        };
+
+        if materials
+            .get(material)
+            .is_some_and(|m| m.base_color != color || m.emissive != emissive)
+        {
+            let material = materials.get_mut(material).unwrap();
+            material.base_color = color;
+            material.emissive = emissive;
+        }
+    }
+}
+
+/// Stops the glow of the dices once their combination is not on display anymore
+pub fn clear_contributing_dices(
+    mut commands: Commands,
+    q_dices: Query<Entity, With<Contributing>>,
+) {
+    for entity in &q_dices {
+        commands.entity(entity).remove::<Contributing>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    /// Physics steps simulated after the throw
+    const NB_STEPS: u32 = 45;
+
+    /// Position and velocity of a dice thrown while frames last `frame_time`,
+    /// once the physics ran the same number of steps
+    fn flight(frame_time: Duration) -> (Vec3, Vec3) {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            PhysicsPlugins::new(FixedPostUpdate),
+        ))
+        .init_resource::<Assets<Mesh>>()
+        .insert_resource(Time::<Fixed>::from_hz(PHYSICS_HZ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(frame_time));
+
+        let dice = Dice::new_6(0);
+        let size = dice.size;
+        let transform = dice.in_hand_transform(Vec3::new(0.0, 10.0, 15.0));
+        let entity = app
+            .world_mut()
+            .spawn((
+                dice,
+                InHandBundle::default(),
+                RigidBody::Dynamic,
+                Collider::cuboid(size, size, size),
+                LinearDamping(LINEAR_DAMPING),
+                LinearVelocity::default(),
+                AngularVelocity::default(),
+                TransformBundle::from_transform(transform),
+            ))
+            .observe(on_roll_dice)
+            .id();
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        app.world_mut().trigger_targets(
+            RollDice::Aim {
+                target: Vec3::new(2.0, 0.0, -3.0),
+                power: 0.5,
+            },
+            entity,
+        );
+        app.world_mut().flush();
+
+        // Same simulated time, whatever the number of frames it takes
+        let fixed_elapsed = |app: &App| app.world().resource::<Time<Fixed>>().elapsed();
+        let end = fixed_elapsed(&app) + Time::<Fixed>::from_hz(PHYSICS_HZ).timestep() * NB_STEPS;
+        while fixed_elapsed(&app) < end {
+            app.update();
+        }
+
+        let world = app.world();
+        (
+            world.get::<Transform>(entity).unwrap().translation,
+            world.get::<LinearVelocity>(entity).unwrap().0,
+        )
+    }
+
+    #[test]
+    fn flight_does_not_depend_on_frame_rate() {
+        let (position_30_fps, velocity_30_fps) = flight(Duration::from_secs_f64(1.0 / 30.0));
+        let (position_144_fps, velocity_144_fps) = flight(Duration::from_secs_f64(1.0 / 144.0));
+
+        assert_ne!(velocity_30_fps, Vec3::ZERO);
+        assert!(position_30_fps.abs_diff_eq(position_144_fps, 1e-3));
+        assert!(velocity_30_fps.abs_diff_eq(velocity_144_fps, 1e-3));
+    }
+
+    #[test]
+    fn aimed_throws_reach_their_target() {
+        let gravity = Vec3::NEG_Y * 9.81;
+        let from = Vec3::new(0.0, 10.0, 15.0);
+        let target = Vec3::new(2.0, 0.5, -1.0);
+
+        for power in [0.0, 0.5, 1.0] {
+            let velocity = aim_velocity(from, target, power, gravity);
+            let trajectory = predict_trajectory(from, velocity, gravity, f32::NEG_INFINITY);
+
+            // Where the preview is once the flight time is over
+            let flight_time = SLOWEST_THROW.lerp(FASTEST_THROW, power);
+            let arrival = trajectory[(flight_time / PREDICTION_STEP).round() as usize];
+            assert!(arrival.distance(target) < 0.1, "{arrival} != {target}");
+        }
     }
 }
@@ -0,0 +1,20 @@
+use std::path::Path;
+
+use bevy::{asset::io::embedded::EmbeddedAssetRegistry, prelude::*};
+
+/// Every file of the `assets/` folder, with its path in it, listed by `build.rs`
+const ASSETS: &[(&str, &[u8])] = include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"));
+
+/// Builds the assets into the executable, in the `embedded://` source that
+/// [`asset_path`](crate::loading::asset_path) points to, to be added after `DefaultPlugins`
+pub struct EmbeddedAssetsPlugin;
+impl Plugin for EmbeddedAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        let assets_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        let registry = app.world().resource::<EmbeddedAssetRegistry>();
+
+        for (path, bytes) in ASSETS {
+            registry.insert_asset(assets_dir.join(path), Path::new(path), *bytes);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+
+use crate::{
+    dice::{Dice, SettledFace},
+    flycam::FlyCam,
+    settings::GameplaySettings,
+};
+
+const LABEL_WIDTH: f32 = 40.0;
+const LABEL_FONT_SIZE: f32 = 36.0;
+/// Height of a label above the center of its dice, in meters
+const LABEL_HEIGHT: f32 = 0.8;
+
+/// Value of a dice, written above it on screen
+#[derive(Component)]
+struct FaceLabel(Entity);
+
+fn spawn_face_labels(mut commands: Commands, q_dices: Query<Entity, Added<Dice>>) {
+    for dice in &q_dices {
+        commands.spawn((
+            FaceLabel(dice),
+            TextBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(LABEL_WIDTH),
+                    ..default()
+                },
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font_size: LABEL_FONT_SIZE,
+                        ..default()
+                    },
+                )
+                .with_justify(JustifyText::Center),
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn update_face_labels(
+    gameplay: Res<GameplaySettings>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<FlyCam>>,
+    q_dices: Query<(&GlobalTransform, Option<&SettledFace>), With<Dice>>,
+    mut q_labels: Query<(&FaceLabel, &mut Style, &mut Text)>,
+) {
+    let Ok((camera, camera_transform)) = q_camera.get_single() else {
+        return;
+    };
+
+    for (FaceLabel(dice), mut style, mut text) in &mut q_labels {
+        // Only settled dices in front of the camera get a label
+        let shown = q_dices
+            .get(*dice)
+            .ok()
+            .filter(|_| gameplay.face_labels)
+            .and_then(|(transform, settled_face)| {
+                let SettledFace(face) = settled_face?;
+                let position = camera.world_to_viewport(
+                    camera_transform,
+                    transform.translation() + Vec3::Y * LABEL_HEIGHT,
+                )?;
+                Some((position, *face))
+            });
+
+        let Some((position, face)) = shown else {
+            if style.display != Display::None {
+                style.display = Display::None;
+            }
+            continue;
+        };
+
+        style.display = Display::Flex;
+        style.left = Val::Px(position.x - LABEL_WIDTH / 2.0);
+        style.top = Val::Px(position.y - LABEL_FONT_SIZE);
+
+        let value = face.to_string();
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+/// Labels with the value read on each settled dice, for bad camera angles
+pub struct FaceLabelPlugin;
+impl Plugin for FaceLabelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (spawn_face_labels, update_face_labels).chain());
+    }
+}
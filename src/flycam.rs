@@ -2,6 +2,9 @@ use bevy::ecs::event::{Events, ManualEventReader};
 use bevy::input::mouse::MouseMotion;
 use bevy::prelude::*;
 use bevy::window::{CursorGrabMode, PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::input::{Action, ActionState};
 
 /// Keeps track of mouse motion events, pitch, and yaw
 #[derive(Resource, Default)]
@@ -10,7 +13,8 @@ struct InputState {
 }
 
 /// Mouse sensitivity and movement speed
-#[derive(Resource)]
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MovementSettings {
     pub sensitivity: f32,
     pub speed: f32,
@@ -25,32 +29,6 @@ impl Default for MovementSettings {
     }
 }
 
-/// Key configuration
-#[derive(Resource)]
-pub struct KeyBindings {
-    pub move_forward: KeyCode,
-    pub move_backward: KeyCode,
-    pub move_left: KeyCode,
-    pub move_right: KeyCode,
-    pub move_ascend: KeyCode,
-    pub move_descend: KeyCode,
-    pub toggle_grab_cursor: KeyCode,
-}
-
-impl Default for KeyBindings {
-    fn default() -> Self {
-        Self {
-            move_forward: KeyCode::KeyW,
-            move_backward: KeyCode::KeyS,
-            move_left: KeyCode::KeyA,
-            move_right: KeyCode::KeyD,
-            move_ascend: KeyCode::Space,
-            move_descend: KeyCode::ShiftLeft,
-            toggle_grab_cursor: KeyCode::Escape,
-        }
-    }
-}
-
 /// Used in queries when you want flycams and not other cameras
 /// A marker component used in queries when you want flycams and not other cameras
 #[derive(Component)]
@@ -69,11 +47,10 @@ fn toggle_grab_cursor(window: &mut Window) {
 
 /// Handles keyboard input and movement
 fn player_move(
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     time: Res<Time>,
     primary_window: Query<&Window, With<PrimaryWindow>>,
     settings: Res<MovementSettings>,
-    key_bindings: Res<KeyBindings>,
     mut query: Query<(&FlyCam, &mut Transform)>, //    mut query: Query<&mut Transform, With<FlyCam>>,
 ) {
     if let Ok(window) = primary_window.get_single() {
@@ -83,21 +60,17 @@ fn player_move(
             let forward = -Vec3::new(local_z.x, 0., local_z.z);
             let right = Vec3::new(local_z.z, 0., -local_z.x);
 
-            for key in keys.get_pressed() {
-                if window.cursor.grab_mode != CursorGrabMode::None {
-                    let key = *key;
-                    if key == key_bindings.move_forward {
-                        velocity += forward;
-                    } else if key == key_bindings.move_backward {
-                        velocity -= forward;
-                    } else if key == key_bindings.move_left {
-                        velocity -= right;
-                    } else if key == key_bindings.move_right {
-                        velocity += right;
-                    } else if key == key_bindings.move_ascend {
-                        velocity += Vec3::Y;
-                    } else if key == key_bindings.move_descend {
-                        velocity -= Vec3::Y;
+            if window.cursor.grab_mode != CursorGrabMode::None {
+                for (action, direction) in [
+                    (Action::MoveForward, forward),
+                    (Action::MoveBackward, -forward),
+                    (Action::MoveLeft, -right),
+                    (Action::MoveRight, right),
+                    (Action::MoveAscend, Vec3::Y),
+                    (Action::MoveDescend, -Vec3::Y),
+                ] {
+                    if actions.pressed(action) {
+                        velocity += direction;
                     }
                 }
             }
@@ -143,12 +116,11 @@ fn player_look(
 }
 
 fn cursor_grab(
-    keys: Res<ButtonInput<KeyCode>>,
-    key_bindings: Res<KeyBindings>,
+    actions: Res<ActionState>,
     mut primary_window: Query<&mut Window, With<PrimaryWindow>>,
 ) {
     if let Ok(mut window) = primary_window.get_single_mut() {
-        if keys.just_pressed(key_bindings.toggle_grab_cursor) {
+        if actions.just_pressed(Action::ToggleGrabCursor) {
             toggle_grab_cursor(&mut window);
         }
     } else {
@@ -162,7 +134,6 @@ impl Plugin for FlyCamPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<InputState>()
             .init_resource::<MovementSettings>()
-            .init_resource::<KeyBindings>()
             .add_systems(Update, (player_move, player_look, cursor_grab));
     }
 }
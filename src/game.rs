@@ -1,4 +1,18 @@
 use bevy::prelude::*;
+use serde::Serialize;
+
+use std::cmp::Ordering;
+
+use crate::{
+    combination::{DiceResult, Roll},
+    dice::{Contributing, Dice},
+    npc::{NPCPickup, NPCThrow},
+    player::{PickupDice, PlayerDice},
+    settings::{GameplaySettings, MatchFormat, TiePolicy, TurnOrder},
+    ui::DisplayScore,
+};
+
+const STARTING_BANKROLL: i32 = 100;
 
 #[derive(Resource)]
 pub struct RetriesLeft(pub u8);
@@ -9,18 +23,528 @@ impl Default for RetriesLeft {
     }
 }
 
+/// Dices the player picked up this round, retries left can be given up with a skip
+#[derive(Resource, Default)]
+pub struct RetriesUsed(pub u8);
+
+/// Dices the NPC can still pick up this round
+#[derive(Resource)]
+pub struct NPCRetriesLeft(pub u8);
+
+impl Default for NPCRetriesLeft {
+    fn default() -> Self {
+        Self(3)
+    }
+}
+
+/// Money of the player, won or lost at the end of each round
+#[derive(Resource)]
+pub struct Bankroll(pub i32);
+
+impl Default for Bankroll {
+    fn default() -> Self {
+        Self(STARTING_BANKROLL)
+    }
+}
+
 #[derive(Resource, Default)]
 pub struct CanSkipTurn(pub bool);
 
 #[derive(States, Default, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum GameState {
+    /// Waiting for the assets and the dice colliders
     #[default]
+    Loading,
+    MainMenu,
+    /// Choosing the opponent
     Setup,
+    /// Deciding who leads the round
+    RoundStart,
     NPCRolling,
     PlayerRolling,
+    /// Both sides tied, each one throws a single dice to break the tie
+    SuddenDeath,
+    /// Both sides are done, showing who won
+    Resolution,
+    /// A side won the match, waiting for a rematch
+    MatchOver,
+    /// The player ran out of money
+    GameOver,
     //TODO: Shopping,
 }
 
-pub fn setup_game_state(mut state: ResMut<NextState<GameState>>) {
-    state.set(GameState::NPCRolling);
+impl GameState {
+    /// Whether a match is being played, and can be paused
+    pub fn is_playing(&self) -> bool {
+        matches!(
+            self,
+            GameState::RoundStart
+                | GameState::NPCRolling
+                | GameState::PlayerRolling
+                | GameState::SuddenDeath
+                | GameState::Resolution
+        )
+    }
+}
+
+/// Freezes the game, physics included, while the pause menu is open
+#[derive(States, Default, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    Player,
+    Npc,
+}
+
+impl Side {
+    pub fn other(self) -> Self {
+        match self {
+            Side::Player => Side::Npc,
+            Side::Npc => Side::Player,
+        }
+    }
+
+    fn rolling_state(self) -> GameState {
+        match self {
+            Side::Player => GameState::PlayerRolling,
+            Side::Npc => GameState::NPCRolling,
+        }
+    }
+}
+
+/// Who plays first in the current round, and what they scored once done
+#[derive(Resource)]
+pub struct Round {
+    pub leader: Side,
+    pub leader_results: Option<Vec<DiceResult>>,
+    /// Winner of the previous round, if any
+    pub last_winner: Option<Side>,
+    /// Player and NPC results waiting for a sudden death
+    pub tie: Option<(Vec<DiceResult>, Vec<DiceResult>)>,
+}
+
+impl Default for Round {
+    fn default() -> Self {
+        Self {
+            leader: Side::Npc,
+            leader_results: None,
+            last_winner: None,
+            tie: None,
+        }
+    }
+}
+
+/// Rounds and points won by each side during the match
+#[derive(Resource, Default)]
+pub struct MatchScore {
+    pub player_wins: u32,
+    pub npc_wins: u32,
+    pub player_points: u32,
+    pub npc_points: u32,
+}
+
+impl MatchScore {
+    /// The score of each side that counts with `format`, player first
+    pub fn scores(&self, format: MatchFormat) -> (u32, u32) {
+        match format {
+            MatchFormat::BestOf(_) => (self.player_wins, self.npc_wins),
+            MatchFormat::FirstTo(_) => (self.player_points, self.npc_points),
+        }
+    }
+
+    pub fn winner(&self, format: MatchFormat) -> Option<Side> {
+        let target = match format {
+            MatchFormat::BestOf(rounds) => u32::from(rounds) / 2 + 1,
+            MatchFormat::FirstTo(points) => points,
+        };
+
+        match self.scores(format) {
+            (player, _) if player >= target => Some(Side::Player),
+            (_, npc) if npc >= target => Some(Side::Npc),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub enum Outcome {
+    Win,
+    Lose,
+    /// Nobody wins, the wager is returned
+    Push,
+}
+
+impl Outcome {
+    /// The same outcome, for the other side
+    pub fn opposite(self) -> Self {
+        match self {
+            Outcome::Win => Outcome::Lose,
+            Outcome::Lose => Outcome::Win,
+            Outcome::Push => Outcome::Push,
+        }
+    }
+}
+
+/// A side is done rolling, with its final results
+#[derive(Event)]
+pub struct TurnOver {
+    pub side: Side,
+    pub results: Vec<DiceResult>,
+    /// The dice of each result
+    pub dices: Vec<Entity>,
+}
+
+pub fn start_round(
+    mut commands: Commands,
+    q_dices: Query<(Entity, Has<PlayerDice>), With<Dice>>,
+    gameplay: Res<GameplaySettings>,
+    mut round: ResMut<Round>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // The dices of the last round would be in the way of the leader throw
+    for (entity, is_player) in &q_dices {
+        if is_player {
+            commands.trigger_targets(PickupDice, entity);
+        } else {
+            commands.trigger_targets(NPCPickup, entity);
+        }
+    }
+
+    round.leader = match gameplay.turn_order {
+        TurnOrder::NPCFirst => Side::Npc,
+        TurnOrder::PlayerFirst => Side::Player,
+        TurnOrder::LoserLeads => round.last_winner.map_or(Side::Npc, Side::other),
+    };
+    round.leader_results = None;
+
+    next_state.set(round.leader.rolling_state());
+}
+
+/// Lets the other side try to beat the leader, or ends the round
+pub fn on_turn_over(
+    trigger: Trigger<TurnOver>,
+    mut commands: Commands,
+    gameplay: Res<GameplaySettings>,
+    mut round: ResMut<Round>,
+    mut bankroll: ResMut<Bankroll>,
+    mut match_score: ResMut<MatchScore>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let TurnOver {
+        side,
+        results,
+        dices,
+    } = trigger.event();
+
+    // Show which dices make the combination
+    let roll = Roll::new(results.clone());
+    for i in roll.combination.contributors(results) {
+        commands.entity(dices[i]).insert(Contributing);
+    }
+
+    let Some(leader_results) = round.leader_results.take() else {
+        commands.trigger(DisplayScore::ToBeat { side: *side, roll });
+        round.leader_results = Some(results.clone());
+        next_state.set(side.other().rolling_state());
+        return;
+    };
+
+    let (player_results, npc_results) = match side {
+        Side::Player => (results.clone(), leader_results),
+        Side::Npc => (leader_results, results.clone()),
+    };
+    let player = Roll::new(player_results.clone());
+    let npc = Roll::new(npc_results.clone());
+
+    let outcome = match player.combination.cmp(&npc.combination) {
+        Ordering::Greater => Outcome::Win,
+        Ordering::Less => Outcome::Lose,
+        Ordering::Equal => match gameplay.tie_policy {
+            TiePolicy::PlayerWins => Outcome::Win,
+            TiePolicy::HouseWins => Outcome::Lose,
+            TiePolicy::Push => Outcome::Push,
+            TiePolicy::SuddenDeath => {
+                round.tie = Some((player_results, npc_results));
+                commands.trigger(DisplayScore::SuddenDeath);
+                next_state.set(GameState::SuddenDeath);
+                return;
+            }
+        },
+    };
+
+    end_round(
+        &mut commands,
+        &mut round,
+        &mut bankroll,
+        &mut match_score,
+        player,
+        npc,
+        outcome,
+        None,
+    );
+
+    //TODO: go to shop
+    next_state.set(GameState::Resolution);
+}
+
+/// Pays the winner and shows the outcome
+fn end_round(
+    commands: &mut Commands,
+    round: &mut Round,
+    bankroll: &mut Bankroll,
+    match_score: &mut MatchScore,
+    player: Roll,
+    npc: Roll,
+    outcome: Outcome,
+    tie_break: Option<(DiceResult, DiceResult)>,
+) {
+    // The loser pays the value of the winning combination
+    let payout = match outcome {
+        Outcome::Win => player.combination.score().max(1) as i32,
+        Outcome::Lose => -(npc.combination.score().max(1) as i32),
+        Outcome::Push => 0,
+    };
+    bankroll.0 += payout;
+
+    // The winner scores the value of its combination
+    match outcome {
+        Outcome::Win => {
+            match_score.player_wins += 1;
+            match_score.player_points += payout.unsigned_abs();
+        }
+        Outcome::Lose => {
+            match_score.npc_wins += 1;
+            match_score.npc_points += payout.unsigned_abs();
+        }
+        Outcome::Push => {}
+    }
+
+    round.last_winner = match outcome {
+        Outcome::Win => Some(Side::Player),
+        Outcome::Lose => Some(Side::Npc),
+        Outcome::Push => round.last_winner,
+    };
+
+    commands.trigger(DisplayScore::Result {
+        npc,
+        player,
+        outcome,
+        payout,
+        tie_break,
+    });
+}
+
+/// The dice each side throws during a sudden death
+#[derive(Component)]
+pub struct SuddenDeathDice;
+
+/// Both sudden death dices settled, with the player and NPC results
+#[derive(Event)]
+pub struct SuddenDeathOver {
+    pub player: DiceResult,
+    pub npc: DiceResult,
+}
+
+pub fn start_sudden_death(
+    mut commands: Commands,
+    q_dices: Query<(Entity, &Dice, Has<PlayerDice>)>,
+    mut retries: ResMut<RetriesLeft>,
+) {
+    for (entity, dice, is_player) in &q_dices {
+        if dice.i == 0 {
+            commands.entity(entity).insert(SuddenDeathDice);
+            take_sudden_death_dice(&mut commands, entity, is_player);
+        }
+    }
+
+    // Only the sudden death dice can be thrown
+    retries.0 = 0;
+}
+
+fn take_sudden_death_dice(commands: &mut Commands, entity: Entity, is_player: bool) {
+    if is_player {
+        commands.trigger_targets(PickupDice, entity);
+    } else {
+        commands.trigger_targets(NPCThrow, entity);
+    }
+}
+
+/// Throws again on a new tie, or ends the round
+pub fn on_sudden_death_over(
+    trigger: Trigger<SuddenDeathOver>,
+    mut commands: Commands,
+    q_dices: Query<(Entity, Has<PlayerDice>), With<SuddenDeathDice>>,
+    mut round: ResMut<Round>,
+    mut bankroll: ResMut<Bankroll>,
+    mut match_score: ResMut<MatchScore>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let SuddenDeathOver { player, npc } = *trigger.event();
+
+    let outcome = match player.cmp(&npc) {
+        Ordering::Greater => Outcome::Win,
+        Ordering::Less => Outcome::Lose,
+        Ordering::Equal => {
+            for (entity, is_player) in &q_dices {
+                take_sudden_death_dice(&mut commands, entity, is_player);
+            }
+            return;
+        }
+    };
+
+    let Some((player_results, npc_results)) = round.tie.take() else {
+        return;
+    };
+
+    end_round(
+        &mut commands,
+        &mut round,
+        &mut bankroll,
+        &mut match_score,
+        Roll::new(player_results),
+        Roll::new(npc_results),
+        outcome,
+        Some((player, npc)),
+    );
+    next_state.set(GameState::Resolution);
+}
+
+pub fn end_sudden_death(mut commands: Commands, q_dices: Query<Entity, With<SuddenDeathDice>>) {
+    for entity in &q_dices {
+        commands.entity(entity).remove::<SuddenDeathDice>();
+    }
+}
+
+/// Starts a new match from scratch
+pub fn reset_match(mut commands: Commands) {
+    commands.insert_resource(MatchScore::default());
+    commands.insert_resource(Round::default());
+}
+
+/// Starts a new run, with a full bankroll
+pub fn reset_bankroll(mut commands: Commands) {
+    commands.insert_resource(Bankroll::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+
+    fn score(player_wins: u32, npc_wins: u32, player_points: u32, npc_points: u32) -> MatchScore {
+        MatchScore {
+            player_wins,
+            npc_wins,
+            player_points,
+            npc_points,
+        }
+    }
+
+    #[test]
+    fn best_of_is_won_by_a_majority_of_rounds() {
+        let format = MatchFormat::BestOf(3);
+
+        assert_eq!(score(1, 1, 0, 0).winner(format), None);
+        assert_eq!(score(2, 1, 0, 0).winner(format), Some(Side::Player));
+        assert_eq!(score(0, 2, 0, 0).winner(format), Some(Side::Npc));
+        // Majority of the rounds of the format, not of the ones played
+        assert_eq!(score(2, 0, 0, 0).winner(MatchFormat::BestOf(4)), None);
+        assert_eq!(
+            score(3, 0, 0, 0).winner(MatchFormat::BestOf(4)),
+            Some(Side::Player)
+        );
+    }
+
+    #[test]
+    fn first_to_is_won_by_points() {
+        let format = MatchFormat::FirstTo(10);
+
+        // Wins don't count, only the points they gave
+        assert_eq!(score(5, 0, 9, 0).winner(format), None);
+        assert_eq!(score(1, 3, 10, 6).winner(format), Some(Side::Player));
+        assert_eq!(score(2, 1, 4, 12).winner(format), Some(Side::Npc));
+    }
+
+    /// Outcome and payout of the last round shown
+    #[derive(Resource, Default)]
+    struct ShownResult(Option<(Outcome, i32)>);
+
+    /// Plays a round where both sides make the same combination
+    fn tied_round(tie_policy: TiePolicy) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .init_state::<GameState>()
+            .init_resource::<Round>()
+            .init_resource::<Bankroll>()
+            .init_resource::<MatchScore>()
+            .init_resource::<ShownResult>()
+            .insert_resource(GameplaySettings {
+                tie_policy,
+                ..default()
+            })
+            .observe(on_turn_over)
+            .observe(
+                |trigger: Trigger<DisplayScore>, mut shown: ResMut<ShownResult>| {
+                    if let DisplayScore::Result {
+                        outcome, payout, ..
+                    } = trigger.event()
+                    {
+                        shown.0 = Some((*outcome, *payout));
+                    }
+                },
+            );
+        // Registers the observers
+        app.world_mut().flush();
+
+        for side in [Side::Npc, Side::Player] {
+            let dices = (0..3).map(|_| app.world_mut().spawn_empty().id()).collect();
+            app.world_mut().trigger(TurnOver {
+                side,
+                results: vec![6, 5, 2],
+                dices,
+            });
+        }
+        app.update();
+
+        app
+    }
+
+    #[test]
+    fn pushes_change_no_score() {
+        let app = tied_round(TiePolicy::Push);
+        let world = app.world();
+
+        assert_eq!(world.resource::<ShownResult>().0, Some((Outcome::Push, 0)));
+        assert_eq!(world.resource::<Bankroll>().0, STARTING_BANKROLL);
+        let match_score = world.resource::<MatchScore>();
+        assert_eq!(match_score.scores(MatchFormat::BestOf(3)), (0, 0));
+        assert_eq!(match_score.scores(MatchFormat::FirstTo(10)), (0, 0));
+        assert_eq!(
+            *world.resource::<State<GameState>>().get(),
+            GameState::Resolution
+        );
+    }
+
+    #[test]
+    fn sudden_death_breaks_ties() {
+        let app = tied_round(TiePolicy::SuddenDeath);
+        let world = app.world();
+
+        assert_eq!(world.resource::<ShownResult>().0, None);
+        assert_eq!(world.resource::<Bankroll>().0, STARTING_BANKROLL);
+        assert_eq!(
+            world.resource::<Round>().tie,
+            Some((vec![6, 5, 2], vec![6, 5, 2]))
+        );
+        assert_eq!(
+            *world.resource::<State<GameState>>().get(),
+            GameState::SuddenDeath
+        );
+    }
 }
@@ -0,0 +1,141 @@
+use bevy::{color::palettes::css::YELLOW, prelude::*};
+
+use crate::{
+    dice::{Cocked, InHand, RollDice},
+    flycam::FlyCam,
+    game::{RetriesLeft, RetriesUsed},
+    input::{Action, ActionState, Binding},
+    player::{throw_dice, PickupDice, PlayerDice, SelectedDice, ThrowPower},
+    table::{TRAY_RADIUS, TRAY_THICKNESS},
+};
+
+const RETICLE_SPEED: f32 = 12.0;
+const RETICLE_MAX_DISTANCE: f32 = TRAY_RADIUS * 0.9;
+const PICKUP_DISTANCE: f32 = 2.0;
+
+/// Where gamepad throws land on the tray
+#[derive(Component)]
+pub struct AimReticle;
+
+pub fn spawn_aim_reticle(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        Name::new("aim_reticle"),
+        AimReticle,
+        PbrBundle {
+            mesh: meshes.add(Torus::new(0.5, 0.7)),
+            material: materials.add(StandardMaterial {
+                base_color: YELLOW.into(),
+                unlit: true,
+                ..default()
+            }),
+            transform: Transform::from_xyz(0.0, TRAY_THICKNESS, 0.0),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ));
+}
+
+pub fn move_aim_reticle(
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    time: Res<Time>,
+    q_camera: Query<&Transform, (With<FlyCam>, Without<AimReticle>)>,
+    mut q_reticle: Query<(&mut Transform, &mut Visibility), With<AimReticle>>,
+) {
+    let (mut transform, mut visibility) = q_reticle.single_mut();
+
+    let Some(gamepad) = gamepads.iter().next() else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    *visibility = Visibility::Visible;
+
+    let stick = Vec2::new(
+        axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+            .unwrap_or_default(),
+        axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+            .unwrap_or_default(),
+    );
+
+    // Move relatively to where the camera looks
+    let camera = q_camera.single();
+    let forward = camera.forward().with_y(0.0).normalize_or_zero();
+    let right = camera.right().with_y(0.0).normalize_or_zero();
+
+    let mut position = transform.translation.xz()
+        + (right.xz() * stick.x + forward.xz() * stick.y) * RETICLE_SPEED * time.delta_seconds();
+    position = position.clamp_length_max(RETICLE_MAX_DISTANCE);
+
+    transform.translation.x = position.x;
+    transform.translation.z = position.y;
+}
+
+pub fn gamepad_throw(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    selected_dice: Option<Res<SelectedDice>>,
+    power: Res<ThrowPower>,
+    q_reticle: Query<&Transform, With<AimReticle>>,
+    q_dices_in_hand: Query<Entity, (With<PlayerDice>, With<InHand>)>,
+) {
+    if !matches!(
+        actions.just_pressed_by(Action::Throw),
+        Some(Binding::Gamepad(_))
+    ) {
+        return;
+    }
+
+    if let Some(selected_dice) = selected_dice {
+        let point = q_reticle.single().translation.with_y(0.0);
+        throw_dice(
+            &mut commands,
+            selected_dice.0,
+            RollDice::Aim {
+                target: point,
+                power: power.0,
+            },
+            &q_dices_in_hand,
+        );
+    }
+}
+
+pub fn gamepad_pickup(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    q_reticle: Query<&Transform, With<AimReticle>>,
+    q_dices_on_table: Query<(Entity, &Transform, Has<Cocked>), (With<PlayerDice>, Without<InHand>)>,
+    mut retries: ResMut<RetriesLeft>,
+    mut retries_used: ResMut<RetriesUsed>,
+) {
+    if !matches!(
+        actions.just_pressed_by(Action::PickUp),
+        Some(Binding::Gamepad(_))
+    ) {
+        return;
+    }
+
+    let reticle = q_reticle.single().translation.xz();
+
+    // Pick up the dice closest to the reticle, cocked dices are free
+    let closest = q_dices_on_table
+        .iter()
+        .filter(|(_, _, cocked)| retries.0 > 0 || *cocked)
+        .map(|(entity, transform, cocked)| {
+            (entity, cocked, transform.translation.xz().distance(reticle))
+        })
+        .filter(|(_, _, distance)| *distance < PICKUP_DISTANCE)
+        .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+
+    if let Some((entity, cocked, _)) = closest {
+        commands.trigger_targets(PickupDice, entity);
+        if !cocked {
+            retries.0 -= 1;
+            retries_used.0 += 1;
+        }
+    }
+}
@@ -0,0 +1,487 @@
+use bevy::{
+    color::palettes::css::{GREEN, RED},
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+use serde::Serialize;
+
+use crate::{
+    combination::{DiceResult, Roll},
+    game::{NPCRetriesLeft, Outcome, RetriesUsed, Side, TurnOver},
+    storage,
+    ui::{spawn_button, spawn_menu, DisplayScore, OpenOverlay, Overlay, OVERLAY_Z_INDEX},
+};
+
+const HISTORY_FONT_SIZE: f32 = 24.0;
+/// Scrolled distance for each line of a mouse wheel, in pixels
+const LINE_HEIGHT: f32 = 30.0;
+const COLUMNS: [(&str, f32); 6] = [
+    ("Round", 80.0),
+    ("NPC", 260.0),
+    ("You", 260.0),
+    ("Retries", 110.0),
+    ("Outcome", 110.0),
+    ("Bankroll", 110.0),
+];
+
+/// The final dices of a side in a past round
+#[derive(Clone, Serialize)]
+pub struct RollRecord {
+    pub dices: Vec<DiceResult>,
+    pub combination: String,
+    pub score: u32,
+    /// Dices picked up again during the round
+    pub retries: u8,
+}
+
+impl RollRecord {
+    fn new(roll: &Roll, retries: u8) -> Self {
+        Self {
+            dices: roll.results.clone(),
+            combination: roll.combination.name(),
+            score: roll.combination.score(),
+            retries,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct RoundRecord {
+    /// Rounds are counted from 1 since the game started
+    pub round: u32,
+    pub npc: RollRecord,
+    pub player: RollRecord,
+    pub outcome: Outcome,
+    /// Change of the bankroll of the player
+    pub payout: i32,
+}
+
+/// Every round played since the game started, oldest first
+#[derive(Resource, Default)]
+pub struct RoundHistory(pub Vec<RoundRecord>);
+
+impl RoundHistory {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.0).unwrap_or_default()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let roll = |roll: &RollRecord| {
+            let dices = roll
+                .dices
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!(
+                "{dices},{},{},{}",
+                csv_field(&roll.combination),
+                roll.score,
+                roll.retries
+            )
+        };
+
+        let header = "round,npc_dices,npc_combination,npc_score,npc_retries,\
+            player_dices,player_combination,player_score,player_retries,outcome,payout\n";
+
+        self.0
+            .iter()
+            .map(|record| {
+                format!(
+                    "{},{},{},{:?},{}\n",
+                    record.round,
+                    roll(&record.npc),
+                    roll(&record.player),
+                    record.outcome,
+                    record.payout
+                )
+            })
+            .fold(header.to_string(), |csv, line| csv + &line)
+    }
+}
+
+/// Quotes a CSV value when it contains separators
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.into()
+    }
+}
+
+/// Retries used by each side in the current round, until it is recorded
+#[derive(Resource, Default)]
+struct RoundRetries {
+    player: u8,
+    npc: u8,
+}
+
+#[derive(Clone, Copy)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Component)]
+enum HistoryButton {
+    Close,
+    Export(ExportFormat),
+}
+
+#[derive(Component)]
+struct HistoryPanel;
+
+/// Rows of the history, moved up and down inside a clipping node
+#[derive(Component, Default)]
+struct HistoryList {
+    position: f32,
+}
+
+#[derive(Component)]
+struct ExportStatusText;
+
+fn record_retries(
+    trigger: Trigger<TurnOver>,
+    retries_used: Res<RetriesUsed>,
+    npc_retries: Res<NPCRetriesLeft>,
+    mut round_retries: ResMut<RoundRetries>,
+) {
+    match trigger.event().side {
+        Side::Player => round_retries.player = retries_used.0,
+        Side::Npc => {
+            round_retries.npc = NPCRetriesLeft::default().0.saturating_sub(npc_retries.0);
+        }
+    }
+}
+
+fn record_round(
+    trigger: Trigger<DisplayScore>,
+    round_retries: Res<RoundRetries>,
+    mut history: ResMut<RoundHistory>,
+) {
+    let DisplayScore::Result {
+        npc,
+        player,
+        outcome,
+        payout,
+        ..
+    } = trigger.event()
+    else {
+        return;
+    };
+
+    let round = history.0.len() as u32 + 1;
+    history.0.push(RoundRecord {
+        round,
+        npc: RollRecord::new(npc, round_retries.npc),
+        player: RollRecord::new(player, round_retries.player),
+        outcome: *outcome,
+        payout: *payout,
+    });
+}
+
+fn setup_history_menu(mut commands: Commands) {
+    let panel = spawn_menu(&mut commands, HistoryPanel, Display::None, |c| {
+        c.spawn(TextBundle::from_section(
+            "History",
+            TextStyle {
+                font_size: 60.0,
+                ..default()
+            },
+        ));
+
+        c.spawn(NodeBundle::default()).with_children(|c| {
+            for (label, width) in COLUMNS {
+                spawn_cell(c, label, width, Color::srgb(0.7, 0.7, 0.7));
+            }
+        });
+
+        c.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                height: Val::Vh(50.0),
+                overflow: Overflow::clip_y(),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|c| {
+            c.spawn((
+                HistoryList::default(),
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(5.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ));
+        });
+
+        c.spawn((
+            ExportStatusText,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: HISTORY_FONT_SIZE,
+                    ..default()
+                },
+            ),
+        ));
+
+        c.spawn(NodeBundle {
+            style: Style {
+                column_gap: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|c| {
+            spawn_button(
+                c,
+                "Export CSV",
+                30.0,
+                HistoryButton::Export(ExportFormat::Csv),
+            );
+            spawn_button(
+                c,
+                "Export JSON",
+                30.0,
+                HistoryButton::Export(ExportFormat::Json),
+            );
+            spawn_button(c, "Close", 30.0, HistoryButton::Close);
+        });
+    });
+
+    commands
+        .entity(panel)
+        .insert(ZIndex::Global(OVERLAY_Z_INDEX));
+}
+
+fn spawn_cell(parent: &mut ChildBuilder, value: impl Into<String>, width: f32, color: Color) {
+    parent.spawn(TextBundle {
+        style: Style {
+            width: Val::Px(width),
+            flex_shrink: 0.0,
+            ..default()
+        },
+        text: Text::from_section(
+            value,
+            TextStyle {
+                font_size: HISTORY_FONT_SIZE,
+                color,
+                ..default()
+            },
+        ),
+        ..default()
+    });
+}
+
+/// Rebuilds the rows, newest round first
+fn fill_history_list(
+    mut commands: Commands,
+    history: Res<RoundHistory>,
+    mut q_list: Query<(Entity, &mut HistoryList, &mut Style)>,
+) {
+    if !history.is_changed() {
+        return;
+    }
+
+    let Ok((entity, mut list, mut style)) = q_list.get_single_mut() else {
+        return;
+    };
+    list.position = 0.0;
+    style.top = Val::Px(0.0);
+
+    commands
+        .entity(entity)
+        .despawn_descendants()
+        .with_children(|c| {
+            if history.0.is_empty() {
+                spawn_cell(c, "No round played yet", COLUMNS[1].1, Color::WHITE);
+            }
+
+            for record in history.0.iter().rev() {
+                let (outcome, color) = match record.outcome {
+                    Outcome::Win => ("Win", GREEN.into()),
+                    Outcome::Lose => ("Lose", RED.into()),
+                    Outcome::Push => ("Push", Color::WHITE),
+                };
+                let values = [
+                    record.round.to_string(),
+                    record.npc.combination.clone(),
+                    record.player.combination.clone(),
+                    format!("{} - {}", record.npc.retries, record.player.retries),
+                    outcome.into(),
+                    format!("{:+}¤", record.payout),
+                ];
+
+                c.spawn(NodeBundle::default()).with_children(|c| {
+                    for (i, (value, (_, width))) in values.into_iter().zip(COLUMNS).enumerate() {
+                        let color = if i >= 4 { color } else { Color::WHITE };
+                        spawn_cell(c, value, width, color);
+                    }
+                });
+            }
+        });
+}
+
+fn scroll_history_list(
+    mut mouse_wheel: EventReader<MouseWheel>,
+    q_panel: Query<&Style, (With<HistoryPanel>, Without<HistoryList>)>,
+    mut q_list: Query<(&mut HistoryList, &mut Style, &Parent, &Node)>,
+    q_nodes: Query<&Node, Without<HistoryList>>,
+) {
+    let shown = q_panel
+        .get_single()
+        .is_ok_and(|style| style.display != Display::None);
+
+    for event in mouse_wheel.read() {
+        if !shown {
+            continue;
+        }
+
+        for (mut list, mut style, parent, node) in &mut q_list {
+            let container_height = q_nodes.get(parent.get()).map_or(0.0, |n| n.size().y);
+            let max_scroll = (node.size().y - container_height).max(0.0);
+
+            let dy = match event.unit {
+                MouseScrollUnit::Line => event.y * LINE_HEIGHT,
+                MouseScrollUnit::Pixel => event.y,
+            };
+            list.position = (list.position + dy).clamp(-max_scroll, 0.0);
+            style.top = Val::Px(list.position);
+        }
+    }
+}
+
+fn open_history(
+    trigger: Trigger<OpenOverlay>,
+    mut q_panel: Query<&mut Style, With<HistoryPanel>>,
+    mut q_status: Query<&mut Text, With<ExportStatusText>>,
+) {
+    if trigger.event().0 == Overlay::History {
+        q_panel.single_mut().display = Display::Flex;
+        q_status.single_mut().sections[0].value.clear();
+    }
+}
+
+fn update_history_buttons(
+    q_btn: Query<(&Interaction, &HistoryButton), Changed<Interaction>>,
+    history: Res<RoundHistory>,
+    mut q_panel: Query<&mut Style, With<HistoryPanel>>,
+    mut q_status: Query<&mut Text, With<ExportStatusText>>,
+) {
+    for (interaction, button) in &q_btn {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            HistoryButton::Close => q_panel.single_mut().display = Display::None,
+            HistoryButton::Export(format) => {
+                let (file_name, content) = match format {
+                    ExportFormat::Csv => ("quatredeuxun-history.csv", history.to_csv()),
+                    ExportFormat::Json => ("quatredeuxun-history.json", history.to_json()),
+                };
+
+                q_status.single_mut().sections[0].value = match storage::export(file_name, &content)
+                {
+                    Some(location) => format!("Exported to {location}"),
+                    None => "Could not export the history".into(),
+                };
+            }
+        }
+    }
+}
+
+/// Every round played, in a scrollable panel that can be exported
+pub struct HistoryPlugin;
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoundHistory>()
+            .init_resource::<RoundRetries>()
+            .observe(record_retries)
+            .observe(record_round)
+            .observe(open_history)
+            .add_systems(Startup, setup_history_menu)
+            .add_systems(
+                Update,
+                (
+                    update_history_buttons,
+                    fill_history_list,
+                    scroll_history_list,
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::*;
+
+    fn record(round: u32, npc: Vec<DiceResult>, player: Vec<DiceResult>) -> RoundRecord {
+        let npc = Roll::new(npc);
+        let player = Roll::new(player);
+        let outcome = match player.combination.cmp(&npc.combination) {
+            Ordering::Greater => Outcome::Win,
+            Ordering::Less => Outcome::Lose,
+            Ordering::Equal => Outcome::Push,
+        };
+
+        RoundRecord {
+            round,
+            npc: RollRecord::new(&npc, 1),
+            player: RollRecord::new(&player, 2),
+            outcome,
+            payout: 0,
+        }
+    }
+
+    #[test]
+    fn quotes_combination_names_with_commas() {
+        let history = RoundHistory(vec![record(1, vec![3, 5, 4], vec![6, 6, 6])]);
+        let csv = history.to_csv();
+        let line = csv.lines().nth(1).unwrap();
+
+        assert_eq!(
+            line,
+            "1,3 5 4,\"Straight 3,4,5\",3,1,6 6 6,6 Strike,6,2,Win,0"
+        );
+    }
+
+    #[test]
+    fn has_one_line_per_round_with_every_column() {
+        let history = RoundHistory(vec![
+            record(1, vec![2, 3, 4], vec![4, 2, 1]),
+            record(2, vec![1, 1, 2], vec![6, 5, 4]),
+        ]);
+        let csv = history.to_csv();
+        let lines = csv.lines().collect::<Vec<_>>();
+        let nb_columns = lines[0].split(',').count();
+
+        assert_eq!(lines.len(), 3);
+        for line in &lines[1..] {
+            // Commas between quotes are part of the value
+            let nb_separators = line
+                .chars()
+                .fold((0, false), |(count, quoted), c| match c {
+                    '"' => (count, !quoted),
+                    ',' if !quoted => (count + 1, quoted),
+                    _ => (count, quoted),
+                })
+                .0;
+            assert_eq!(nb_separators + 1, nb_columns, "{line}");
+        }
+    }
+
+    #[test]
+    fn escapes_quotes() {
+        assert_eq!(csv_field("6 Strike"), "6 Strike");
+        assert_eq!(csv_field("Straight 1,2,3"), "\"Straight 1,2,3\"");
+        assert_eq!(csv_field("a \"b\""), "\"a \"\"b\"\"\"");
+    }
+}
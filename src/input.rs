@@ -0,0 +1,344 @@
+use std::{collections::BTreeMap, fmt};
+
+use bevy::{
+    ecs::system::SystemParam,
+    input::InputSystem,
+    prelude::*,
+    ui::UiSystem,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+/// Everything the player can do, whatever the input device
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub enum Action {
+    /// Select a dice in hand, or throw the selected one
+    Throw,
+    /// Pick up a dice from the table
+    PickUp,
+    /// Select the next dice in hand
+    SelectDice,
+    Punch,
+    SkipTurn,
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    MoveAscend,
+    MoveDescend,
+    ToggleGrabCursor,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Self; 13] = [
+        Self::Throw,
+        Self::PickUp,
+        Self::SelectDice,
+        Self::Punch,
+        Self::SkipTurn,
+        Self::MoveForward,
+        Self::MoveBackward,
+        Self::MoveLeft,
+        Self::MoveRight,
+        Self::MoveAscend,
+        Self::MoveDescend,
+        Self::ToggleGrabCursor,
+        Self::Pause,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Throw => "Select / throw",
+            Action::PickUp => "Pick up",
+            Action::SelectDice => "Next dice",
+            Action::Punch => "Punch the table",
+            Action::SkipTurn => "Stop there",
+            Action::MoveForward => "Camera forward",
+            Action::MoveBackward => "Camera backward",
+            Action::MoveLeft => "Camera left",
+            Action::MoveRight => "Camera right",
+            Action::MoveAscend => "Camera up",
+            Action::MoveDescend => "Camera down",
+            Action::ToggleGrabCursor => "Grab cursor",
+            Action::Pause => "Pause",
+        }
+    }
+}
+
+/// A physical input that can trigger an [`Action`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+    Touch,
+}
+
+impl Binding {
+    fn pressed(self, inputs: &Inputs) -> bool {
+        match self {
+            Binding::Key(key) => inputs.keys.pressed(key),
+            Binding::Mouse(button) => inputs.mouse.pressed(button),
+            Binding::Gamepad(button) => inputs.gamepads.iter().any(|gamepad| {
+                inputs
+                    .gamepad_buttons
+                    .pressed(GamepadButton::new(gamepad, button))
+            }),
+            Binding::Touch => inputs.touches.iter().next().is_some(),
+        }
+    }
+
+    fn just_pressed(self, inputs: &Inputs) -> bool {
+        match self {
+            Binding::Key(key) => inputs.keys.just_pressed(key),
+            Binding::Mouse(button) => inputs.mouse.just_pressed(button),
+            Binding::Gamepad(button) => inputs.gamepads.iter().any(|gamepad| {
+                inputs
+                    .gamepad_buttons
+                    .just_pressed(GamepadButton::new(gamepad, button))
+            }),
+            Binding::Touch => inputs.touches.any_just_pressed(),
+        }
+    }
+
+    fn same_device(self, other: Self) -> bool {
+        std::mem::discriminant(&self) == std::mem::discriminant(&other)
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                write!(f, "{}", name.strip_prefix("Key").unwrap_or(&name))
+            }
+            Binding::Mouse(button) => write!(f, "Mouse {button:?}"),
+            Binding::Gamepad(button) => write!(f, "Pad {button:?}"),
+            Binding::Touch => write!(f, "Touch"),
+        }
+    }
+}
+
+/// Bindings of every action, saved with the settings
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct ActionMap(BTreeMap<Action, Vec<Binding>>);
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        use Binding::{Gamepad, Key, Mouse, Touch};
+
+        Self(BTreeMap::from([
+            (
+                Action::Throw,
+                vec![
+                    Mouse(MouseButton::Left),
+                    Touch,
+                    Gamepad(GamepadButtonType::South),
+                ],
+            ),
+            (
+                Action::PickUp,
+                vec![Mouse(MouseButton::Right), Gamepad(GamepadButtonType::West)],
+            ),
+            (
+                Action::SelectDice,
+                vec![Key(KeyCode::Tab), Gamepad(GamepadButtonType::RightTrigger)],
+            ),
+            (
+                Action::Punch,
+                vec![Key(KeyCode::Space), Gamepad(GamepadButtonType::North)],
+            ),
+            (
+                Action::SkipTurn,
+                vec![Key(KeyCode::Enter), Gamepad(GamepadButtonType::Start)],
+            ),
+            (Action::MoveForward, vec![Key(KeyCode::KeyW)]),
+            (Action::MoveBackward, vec![Key(KeyCode::KeyS)]),
+            (Action::MoveLeft, vec![Key(KeyCode::KeyA)]),
+            (Action::MoveRight, vec![Key(KeyCode::KeyD)]),
+            (Action::MoveAscend, vec![Key(KeyCode::KeyE)]),
+            (Action::MoveDescend, vec![Key(KeyCode::ShiftLeft)]),
+            (Action::ToggleGrabCursor, vec![Key(KeyCode::KeyG)]),
+            (
+                Action::Pause,
+                vec![Key(KeyCode::Escape), Gamepad(GamepadButtonType::Select)],
+            ),
+        ]))
+    }
+}
+
+impl ActionMap {
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Adds the default bindings of actions missing from the map, left out of a hand-edited file
+    pub fn fill_missing(&mut self) {
+        for (action, bindings) in Self::default().0 {
+            self.0.entry(action).or_insert(bindings);
+        }
+    }
+
+    /// Replaces the bindings of `action` coming from the same device as `binding`
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.0.entry(action).or_default();
+        bindings.retain(|b| !b.same_device(binding));
+        bindings.push(binding);
+    }
+
+    /// Other actions sharing a binding with `action`
+    pub fn conflicts(&self, action: Action) -> Vec<Action> {
+        let bindings = self.bindings(action);
+
+        self.0
+            .iter()
+            .filter(|(other, other_bindings)| {
+                **other != action && other_bindings.iter().any(|b| bindings.contains(b))
+            })
+            .map(|(other, _)| *other)
+            .collect()
+    }
+}
+
+/// Actions triggered this frame, along with the binding that triggered them
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashMap<Action, Binding>,
+    just_pressed: HashMap<Action, Binding>,
+    just_released: HashSet<Action>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains_key(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains_key(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    /// The binding that triggered `action` this frame, if any
+    pub fn just_pressed_by(&self, action: Action) -> Option<Binding> {
+        self.just_pressed.get(&action).copied()
+    }
+}
+
+/// The action waiting for a new binding from the rebinding screen
+#[derive(Resource)]
+pub struct Rebinding(pub Action);
+
+#[derive(SystemParam)]
+struct Inputs<'w> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+    gamepads: Res<'w, Gamepads>,
+    touches: Res<'w, Touches>,
+}
+
+fn update_action_state(
+    inputs: Inputs,
+    action_map: Res<ActionMap>,
+    rebinding: Option<Res<Rebinding>>,
+    mut state: ResMut<ActionState>,
+) {
+    let previously_pressed = std::mem::take(&mut state.pressed);
+    state.just_pressed.clear();
+
+    // Inputs are captured by the rebinding screen
+    if rebinding.is_none() {
+        for (action, bindings) in &action_map.0 {
+            for binding in bindings {
+                if binding.pressed(&inputs) {
+                    state.pressed.insert(*action, *binding);
+                }
+
+                if binding.just_pressed(&inputs) {
+                    state.just_pressed.insert(*action, *binding);
+                }
+            }
+        }
+    }
+
+    state.just_released = previously_pressed
+        .into_keys()
+        .filter(|action| !state.pressed.contains_key(action))
+        .collect();
+}
+
+fn capture_rebinding(
+    mut commands: Commands,
+    inputs: Inputs,
+    rebinding: Option<Res<Rebinding>>,
+    q_interactions: Query<&Interaction>,
+    mut action_map: ResMut<ActionMap>,
+) {
+    // Skip the frame of the click that started the rebinding
+    let Some(rebinding) = rebinding.filter(|r| !r.is_added()) else {
+        return;
+    };
+
+    let cancelled = inputs.keys.just_pressed(KeyCode::Escape)
+        || inputs
+            .gamepad_buttons
+            .get_just_pressed()
+            .any(|button| button.button_type == GamepadButtonType::Select);
+    if cancelled {
+        commands.remove_resource::<Rebinding>();
+        return;
+    }
+
+    // Clicks on the menus are not bindings
+    let over_ui = q_interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+
+    let binding = inputs
+        .keys
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            inputs
+                .mouse
+                .get_just_pressed()
+                .next()
+                .filter(|_| !over_ui)
+                .map(|button| Binding::Mouse(*button))
+        })
+        .or_else(|| {
+            inputs
+                .gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Gamepad(button.button_type))
+        });
+
+    if let Some(binding) = binding {
+        action_map.rebind(rebinding.0, binding);
+        commands.remove_resource::<Rebinding>();
+    }
+}
+
+/// Maps keyboard, mouse, touch and gamepad inputs to game actions
+pub struct InputPlugin;
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionMap>()
+            .init_resource::<ActionState>()
+            .add_systems(
+                PreUpdate,
+                // The input ending a rebinding doesn't trigger an action too
+                (update_action_state, capture_rebinding)
+                    .chain()
+                    .after(InputSystem)
+                    .after(UiSystem::Focus),
+            );
+    }
+}
@@ -0,0 +1,161 @@
+use avian3d::prelude::*;
+use bevy::{
+    asset::{io::embedded::EMBEDDED, AssetPath, RecursiveDependencyLoadState, UntypedAssetId},
+    gltf::Gltf,
+    prelude::*,
+};
+
+use crate::{
+    dice::{Dice, DICE_ASSET, NB_DICES},
+    game::GameState,
+    table::TABLE_ASSET,
+    ui::{spawn_menu, FONT_ASSET},
+};
+
+const PROGRESS_BAR_WIDTH: f32 = 400.0;
+
+/// Path of a file of `assets/`, which is built into the executable with the `embedded_assets` feature
+pub fn asset_path(path: impl Into<AssetPath<'static>>) -> AssetPath<'static> {
+    if cfg!(all(feature = "embedded_assets", not(feature = "dev"))) {
+        path.into().with_source(EMBEDDED)
+    } else {
+        path.into()
+    }
+}
+
+/// Everything the game needs before it starts
+#[derive(Resource)]
+pub struct GameAssets {
+    pub dice: Handle<Gltf>,
+    pub table: Handle<Gltf>,
+    pub font: Handle<Font>,
+}
+
+impl FromWorld for GameAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        Self {
+            dice: asset_server.load(asset_path(DICE_ASSET)),
+            table: asset_server.load(asset_path(TABLE_ASSET)),
+            font: asset_server.load(asset_path(FONT_ASSET)),
+        }
+    }
+}
+
+impl GameAssets {
+    fn ids(&self) -> [UntypedAssetId; 3] {
+        [
+            self.dice.id().untyped(),
+            self.table.id().untyped(),
+            self.font.id().untyped(),
+        ]
+    }
+}
+
+/// Every game asset is either loaded or missing, the table and the dices can be spawned
+#[derive(Resource)]
+pub struct AssetsLoaded;
+
+#[derive(Component)]
+struct ProgressBar;
+
+fn spawn_loading_screen(mut commands: Commands) {
+    spawn_menu(
+        &mut commands,
+        StateScoped(GameState::Loading),
+        Display::Flex,
+        |c| {
+            c.spawn(TextBundle::from_section(
+                "Loading...",
+                TextStyle {
+                    font_size: 50.0,
+                    ..default()
+                },
+            ));
+
+            c.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(PROGRESS_BAR_WIDTH),
+                    height: Val::Px(20.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(Color::WHITE),
+                ..default()
+            })
+            .with_children(|c| {
+                c.spawn((
+                    ProgressBar,
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: Color::WHITE.into(),
+                        ..default()
+                    },
+                ));
+            });
+        },
+    );
+}
+
+fn track_loading(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
+    assets_loaded: Option<Res<AssetsLoaded>>,
+    q_dices: Query<Entity, With<Dice>>,
+    q_children: Query<&Children>,
+    q_colliders: Query<(), With<Collider>>,
+    mut q_bar: Query<&mut Style, With<ProgressBar>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // A failed asset is done too, it gets replaced by a procedural one
+    let nb_assets = game_assets.ids().len();
+    let assets_done = game_assets
+        .ids()
+        .into_iter()
+        .filter(|id| {
+            matches!(
+                asset_server.recursive_dependency_load_state(*id),
+                RecursiveDependencyLoadState::Loaded | RecursiveDependencyLoadState::Failed
+            )
+        })
+        .count();
+    if assets_done == nb_assets && assets_loaded.is_none() {
+        commands.insert_resource(AssetsLoaded);
+    }
+
+    // Dices can only be thrown once their colliders are built
+    let nb_dices = NB_DICES * 2;
+    let dices_ready = q_dices
+        .iter()
+        .filter(|entity| {
+            q_children
+                .iter_descendants(*entity)
+                .any(|c| q_colliders.contains(c))
+        })
+        .count();
+
+    let progress = (assets_done + dices_ready) as f32 / (nb_assets + nb_dices) as f32;
+    for mut style in &mut q_bar {
+        style.width = Val::Percent(progress * 100.0);
+    }
+
+    if dices_ready == nb_dices {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+/// Waits for the game assets and the dice colliders before showing the main menu
+pub struct LoadingPlugin;
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameAssets>()
+            .add_systems(OnEnter(GameState::Loading), spawn_loading_screen)
+            .add_systems(Update, track_loading.run_if(in_state(GameState::Loading)));
+    }
+}
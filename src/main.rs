@@ -1,66 +1,155 @@
 use avian3d::prelude::*;
 use bevy::prelude::*;
-use dice::analyze_dices;
-use game::{setup_game_state, CanSkipTurn, GameState, RetriesLeft};
-use npc::{reroll_fallen_npc_dices, roll_npc_dices, spawn_npc_dices};
+use dice::{analyze_dices, clear_contributing_dices, recover_stuck_dices, update_dice_tints};
+use face_label::FaceLabelPlugin;
+use game::{
+    end_sudden_death, on_sudden_death_over, on_turn_over, reset_match, start_round,
+    start_sudden_death, Bankroll, CanSkipTurn, GameState, MatchScore, NPCRetriesLeft, PauseState,
+    RetriesLeft, RetriesUsed, Round,
+};
+use gamepad::{gamepad_pickup, gamepad_throw, move_aim_reticle, spawn_aim_reticle};
+use history::HistoryPlugin;
+use input::InputPlugin;
+use loading::{AssetsLoaded, LoadingPlugin};
+use menu::MenuPlugin;
+use music::MusicPlugin;
+use npc::{
+    cancel_npc_throws, reroll_fallen_npc_dices, roll_npc_dices, spawn_npc_dices,
+    throw_npc_dices_in_hand,
+};
 use player::{
     click_spawns_raycast, manage_selected_dice_animation, pickup_all_player_dices,
-    pickup_fallen_dices, raycast_dices, spawn_camera, spawn_player_dices,
+    pickup_fallen_dices, preview_throw, raycast_dices, release_drag_gesture, select_next_dice,
+    spawn_camera, spawn_player_dices, ThrowPower,
 };
+use profile::ProfilePlugin;
+use reveal::RevealPlugin;
+use settings::SettingsPlugin;
+use sfx::SfxPlugin;
+use stats::StatsPlugin;
 use table::{punch_table, setup};
 use ui::UiPlugin;
 
 mod combination;
 mod dice;
+#[cfg(all(feature = "embedded_assets", not(feature = "dev")))]
+mod embedded;
+mod face_label;
 mod flycam;
 mod game;
+mod gamepad;
+mod history;
+mod input;
+mod loading;
+mod menu;
+mod music;
 mod npc;
 mod player;
+mod profile;
+mod reveal;
+mod settings;
+mod sfx;
+mod stats;
+mod storage;
 mod table;
 mod ui;
 
 fn main() {
-    App::new()
-        .add_plugins((
-            DefaultPlugins
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        fit_canvas_to_parent: true,
-                        ..default()
-                    }),
-                    ..default()
-                })
-                .set(AssetPlugin {
-                    meta_check: bevy::asset::AssetMetaCheck::Never,
+    let mut app = App::new();
+
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    fit_canvas_to_parent: true,
                     ..default()
                 }),
-            PhysicsPlugins::default(),
-            UiPlugin,
-            flycam::FlyCamPlugin,
-            //bevy_inspector_egui::quick::WorldInspectorPlugin::new(),
-        ))
-        .add_systems(
-            Startup,
-            (setup, spawn_camera, spawn_player_dices, spawn_npc_dices),
-        )
-        .add_systems(
-            Update,
+                ..default()
+            })
+            .set(AssetPlugin {
+                meta_check: bevy::asset::AssetMetaCheck::Never,
+                ..default()
+            }),
+    );
+
+    #[cfg(all(feature = "embedded_assets", not(feature = "dev")))]
+    app.add_plugins(embedded::EmbeddedAssetsPlugin);
+
+    app.add_plugins((
+        // Same simulation whatever the frame rate
+        PhysicsPlugins::new(FixedPostUpdate),
+        LoadingPlugin,
+        UiPlugin,
+        InputPlugin,
+        SettingsPlugin,
+        MenuPlugin,
+        ProfilePlugin,
+        SfxPlugin,
+        MusicPlugin,
+        RevealPlugin,
+        FaceLabelPlugin,
+        HistoryPlugin,
+        StatsPlugin,
+        flycam::FlyCamPlugin,
+        //bevy_inspector_egui::quick::WorldInspectorPlugin::new(),
+    ))
+    .add_systems(Startup, (spawn_camera, spawn_aim_reticle))
+    .add_systems(
+        Update,
+        (setup, spawn_player_dices, spawn_npc_dices).run_if(resource_added::<AssetsLoaded>),
+    )
+    .add_systems(
+        Update,
+        (
             (
-                setup_game_state.run_if(in_state(GameState::Setup)),
-                (pickup_fallen_dices, click_spawns_raycast, raycast_dices)
-                    .run_if(in_state(GameState::PlayerRolling)),
-                analyze_dices,
-                manage_selected_dice_animation,
-                punch_table,
-                reroll_fallen_npc_dices,
-            ),
-        )
-        .add_systems(
-            OnEnter(GameState::NPCRolling),
-            (pickup_all_player_dices, roll_npc_dices),
+                pickup_fallen_dices,
+                click_spawns_raycast,
+                release_drag_gesture,
+                raycast_dices,
+                select_next_dice,
+                preview_throw,
+                gamepad_throw,
+                gamepad_pickup,
+            )
+                .run_if(
+                    in_state(GameState::PlayerRolling).or_else(in_state(GameState::SuddenDeath)),
+                ),
+            move_aim_reticle,
+            analyze_dices,
+            recover_stuck_dices,
+            update_dice_tints,
+            manage_selected_dice_animation,
+            punch_table,
+            reroll_fallen_npc_dices,
+            throw_npc_dices_in_hand
+                .run_if(in_state(GameState::NPCRolling).or_else(in_state(GameState::SuddenDeath))),
         )
-        .init_state::<GameState>()
-        .init_resource::<RetriesLeft>()
-        .init_resource::<CanSkipTurn>()
-        .run();
+            .run_if(in_state(PauseState::Running)),
+    )
+    .add_systems(OnEnter(GameState::RoundStart), start_round)
+    .add_systems(OnExit(GameState::Resolution), clear_contributing_dices)
+    .add_systems(OnEnter(GameState::MainMenu), clear_contributing_dices)
+    .add_systems(OnEnter(GameState::NPCRolling), roll_npc_dices)
+    .add_systems(OnExit(GameState::NPCRolling), cancel_npc_throws)
+    .add_systems(OnEnter(GameState::PlayerRolling), pickup_all_player_dices)
+    .add_systems(OnEnter(GameState::SuddenDeath), start_sudden_death)
+    .add_systems(
+        OnExit(GameState::SuddenDeath),
+        (end_sudden_death, cancel_npc_throws),
+    )
+    .add_systems(OnExit(GameState::MatchOver), reset_match)
+    .observe(on_turn_over)
+    .observe(on_sudden_death_over)
+    .insert_resource(Time::<Fixed>::from_hz(dice::PHYSICS_HZ))
+    .init_state::<GameState>()
+    .enable_state_scoped_entities::<GameState>()
+    .init_resource::<RetriesLeft>()
+    .init_resource::<RetriesUsed>()
+    .init_resource::<NPCRetriesLeft>()
+    .init_resource::<Round>()
+    .init_resource::<Bankroll>()
+    .init_resource::<MatchScore>()
+    .init_resource::<CanSkipTurn>()
+    .init_resource::<ThrowPower>()
+    .run();
 }
@@ -0,0 +1,233 @@
+use avian3d::prelude::*;
+use bevy::{
+    prelude::*,
+    window::{CursorGrabMode, PrimaryWindow},
+};
+
+use crate::{
+    game::{reset_bankroll, reset_match, Bankroll, GameState, MatchScore, PauseState},
+    input::{Action, ActionState},
+    settings::{GameplaySettings, MatchFormat},
+    ui::{spawn_button, spawn_menu, spawn_overlay_button, Overlay},
+};
+
+#[derive(Component)]
+pub enum MenuButton {
+    Play,
+    /// Switches to the next match format
+    Mode,
+    Resume,
+    /// Leaves the current match
+    MainMenu,
+    #[cfg(not(target_arch = "wasm32"))]
+    Quit,
+}
+
+#[derive(Component)]
+struct ModeText;
+
+fn spawn_title(parent: &mut ChildBuilder, title: &str) {
+    parent.spawn(TextBundle::from_section(
+        title,
+        TextStyle {
+            font_size: 80.0,
+            ..default()
+        },
+    ));
+}
+
+fn spawn_main_menu(mut commands: Commands) {
+    spawn_menu(
+        &mut commands,
+        StateScoped(GameState::MainMenu),
+        Display::Flex,
+        |c| {
+            spawn_title(c, "Quatre-Deux-Un");
+
+            spawn_button(c, "Play", 50.0, MenuButton::Play);
+
+            c.spawn(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|c| {
+                spawn_button(c, "Mode", 40.0, MenuButton::Mode);
+                c.spawn((
+                    ModeText,
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 40.0,
+                            ..default()
+                        },
+                    ),
+                ));
+            });
+
+            spawn_overlay_button(c, Overlay::Settings, 40.0);
+            spawn_overlay_button(c, Overlay::History, 40.0);
+            spawn_overlay_button(c, Overlay::Stats, 40.0);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            spawn_button(c, "Quit", 40.0, MenuButton::Quit);
+        },
+    );
+}
+
+fn spawn_pause_menu(mut commands: Commands) {
+    spawn_menu(
+        &mut commands,
+        StateScoped(PauseState::Paused),
+        Display::Flex,
+        |c| {
+            spawn_title(c, "Paused");
+            spawn_button(c, "Resume", 40.0, MenuButton::Resume);
+            spawn_overlay_button(c, Overlay::Settings, 40.0);
+            spawn_overlay_button(c, Overlay::History, 40.0);
+            spawn_button(c, "Main menu", 40.0, MenuButton::MainMenu);
+        },
+    );
+}
+
+fn spawn_game_over_screen(
+    mut commands: Commands,
+    gameplay: Res<GameplaySettings>,
+    match_score: Res<MatchScore>,
+    bankroll: Res<Bankroll>,
+) {
+    let (player, npc) = match_score.scores(gameplay.match_format);
+
+    spawn_menu(
+        &mut commands,
+        StateScoped(GameState::GameOver),
+        Display::Flex,
+        |c| {
+            spawn_title(c, "Game over");
+
+            c.spawn(
+                TextBundle::from_section(
+                    format!(
+                        "You ran out of money\nBankroll: {}¤\n{}: you {player} - {npc} NPC\nRounds won: {} - lost: {}",
+                        bankroll.0,
+                        gameplay.match_format.label(),
+                        match_score.player_wins,
+                        match_score.npc_wins,
+                    ),
+                    TextStyle {
+                        font_size: 40.0,
+                        ..default()
+                    },
+                )
+                .with_text_justify(JustifyText::Center),
+            );
+
+            spawn_button(c, "Main menu", 40.0, MenuButton::MainMenu);
+        },
+    );
+}
+
+fn update_menu_buttons(
+    q_btn: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut gameplay: ResMut<GameplaySettings>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    #[cfg(not(target_arch = "wasm32"))] mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button) in &q_btn {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            MenuButton::Play => next_state.set(GameState::Setup),
+            MenuButton::Mode => {
+                let i = MatchFormat::ALL
+                    .iter()
+                    .position(|format| *format == gameplay.match_format)
+                    .unwrap_or_default();
+                gameplay.match_format = MatchFormat::ALL[(i + 1) % MatchFormat::ALL.len()];
+            }
+            MenuButton::Resume => next_pause_state.set(PauseState::Running),
+            MenuButton::MainMenu => {
+                next_pause_state.set(PauseState::Running);
+                next_state.set(GameState::MainMenu);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            MenuButton::Quit => {
+                exit.send(AppExit::Success);
+            }
+        }
+    }
+}
+
+fn update_mode_text(mut query: Query<(&mut Text, Ref<ModeText>)>, gameplay: Res<GameplaySettings>) {
+    for (mut text, mode_text) in &mut query {
+        if mode_text.is_added() || gameplay.is_changed() {
+            text.sections[0].value = gameplay.match_format.label();
+        }
+    }
+}
+
+fn toggle_pause(
+    actions: Res<ActionState>,
+    state: Res<State<GameState>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if !actions.just_pressed(Action::Pause) || !state.get().is_playing() {
+        return;
+    }
+
+    next_pause_state.set(match pause_state.get() {
+        PauseState::Running => PauseState::Paused,
+        PauseState::Paused => PauseState::Running,
+    });
+}
+
+fn pause_game(
+    mut physics_time: ResMut<Time<Physics>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut primary_window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    physics_time.pause();
+    virtual_time.pause();
+
+    // Give the cursor back to the menu
+    if let Ok(mut window) = primary_window.get_single_mut() {
+        window.cursor.grab_mode = CursorGrabMode::None;
+        window.cursor.visible = true;
+    }
+}
+
+fn resume_game(mut physics_time: ResMut<Time<Physics>>, mut virtual_time: ResMut<Time<Virtual>>) {
+    physics_time.unpause();
+    virtual_time.unpause();
+}
+
+/// Main menu, pause menu and game over screen
+pub struct MenuPlugin;
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<PauseState>()
+            .enable_state_scoped_entities::<PauseState>()
+            .add_systems(
+                OnEnter(GameState::MainMenu),
+                (spawn_main_menu, reset_match, reset_bankroll),
+            )
+            .add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
+            .add_systems(OnEnter(PauseState::Paused), (spawn_pause_menu, pause_game))
+            .add_systems(OnExit(PauseState::Paused), resume_game)
+            .add_systems(
+                Update,
+                (
+                    update_menu_buttons,
+                    update_mode_text.run_if(in_state(GameState::MainMenu)),
+                    toggle_pause,
+                ),
+            );
+    }
+}
@@ -0,0 +1,189 @@
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    game::{GameState, Outcome},
+    loading::asset_path,
+    settings::{AudioChannel, AudioSettings},
+    ui::DisplayScore,
+};
+
+/// Time for a track to fade in or out, in seconds
+const CROSSFADE: f32 = 1.5;
+/// Ambience level, under the music
+const AMBIENCE_VOLUME: f32 = 0.4;
+/// Music level while a stinger plays
+const DUCKED_VOLUME: f32 = 0.3;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Track {
+    Menu,
+    NPCTurn,
+    PlayerTurn,
+}
+
+impl Track {
+    /// Track for a state, `None` to keep the current one
+    fn for_state(state: &GameState) -> Option<Self> {
+        match state {
+            GameState::Loading
+            | GameState::MainMenu
+            | GameState::Setup
+            | GameState::MatchOver
+            | GameState::GameOver => Some(Track::Menu),
+            GameState::NPCRolling => Some(Track::NPCTurn),
+            GameState::PlayerRolling | GameState::SuddenDeath => Some(Track::PlayerTurn),
+            GameState::RoundStart | GameState::Resolution => None,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct MusicAssets {
+    menu: Handle<AudioSource>,
+    npc_turn: Handle<AudioSource>,
+    player_turn: Handle<AudioSource>,
+    ambience: Handle<AudioSource>,
+    win: Handle<AudioSource>,
+    lose: Handle<AudioSource>,
+}
+
+impl FromWorld for MusicAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        Self {
+            menu: asset_server.load(asset_path("music/menu.wav")),
+            npc_turn: asset_server.load(asset_path("music/npc_turn.wav")),
+            player_turn: asset_server.load(asset_path("music/player_turn.wav")),
+            ambience: asset_server.load(asset_path("music/ambience.wav")),
+            win: asset_server.load(asset_path("music/win.wav")),
+            lose: asset_server.load(asset_path("music/lose.wav")),
+        }
+    }
+}
+
+impl MusicAssets {
+    fn track(&self, track: Track) -> Handle<AudioSource> {
+        match track {
+            Track::Menu => self.menu.clone(),
+            Track::NPCTurn => self.npc_turn.clone(),
+            Track::PlayerTurn => self.player_turn.clone(),
+        }
+    }
+}
+
+/// A looping track, with how much it is faded in from 0 to 1
+#[derive(Component)]
+struct MusicTrack {
+    track: Track,
+    fade: f32,
+}
+
+#[derive(Component)]
+struct Ambience;
+
+#[derive(Component)]
+struct Stinger;
+
+fn start_ambience(mut commands: Commands, music: Res<MusicAssets>) {
+    commands.spawn((
+        Ambience,
+        AudioBundle {
+            source: music.ambience.clone(),
+            settings: PlaybackSettings::LOOP.with_volume(Volume::ZERO),
+        },
+    ));
+}
+
+fn crossfade_music(
+    mut commands: Commands,
+    mut current: Local<Option<Track>>,
+    time: Res<Time<Real>>,
+    state: Res<State<GameState>>,
+    audio: Res<AudioSettings>,
+    music: Res<MusicAssets>,
+    mut q_tracks: Query<(Entity, &mut MusicTrack, Option<&AudioSink>)>,
+    q_ambience: Query<&AudioSink, With<Ambience>>,
+    q_stingers: Query<(), With<Stinger>>,
+) {
+    if let Some(track) = Track::for_state(state.get()) {
+        // A track fading out fades back in
+        let playing = q_tracks
+            .iter()
+            .any(|(_, music_track, _)| music_track.track == track);
+        if !playing {
+            commands.spawn((
+                MusicTrack { track, fade: 0.0 },
+                AudioBundle {
+                    source: music.track(track),
+                    settings: PlaybackSettings::LOOP.with_volume(Volume::ZERO),
+                },
+            ));
+        }
+        *current = Some(track);
+    }
+
+    let ducking = if q_stingers.is_empty() {
+        1.0
+    } else {
+        DUCKED_VOLUME
+    };
+    let volume = |level| audio.sink_volume(AudioChannel::Music, level);
+    let step = time.delta_seconds() / CROSSFADE;
+
+    for (entity, mut music_track, sink) in &mut q_tracks {
+        if Some(music_track.track) == *current {
+            music_track.fade = (music_track.fade + step).min(1.0);
+        } else {
+            music_track.fade -= step;
+            if music_track.fade <= 0.0 {
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+        }
+
+        if let Some(sink) = sink {
+            sink.set_volume(volume(music_track.fade * ducking));
+        }
+    }
+
+    for sink in &q_ambience {
+        sink.set_volume(volume(AMBIENCE_VOLUME));
+    }
+}
+
+fn play_stinger(
+    trigger: Trigger<DisplayScore>,
+    mut commands: Commands,
+    audio: Res<AudioSettings>,
+    music: Res<MusicAssets>,
+) {
+    let DisplayScore::Result { outcome, .. } = trigger.event() else {
+        return;
+    };
+    let source = match outcome {
+        Outcome::Win => music.win.clone(),
+        Outcome::Lose => music.lose.clone(),
+        Outcome::Push => return,
+    };
+
+    commands.spawn((
+        Stinger,
+        AudioBundle {
+            source,
+            settings: PlaybackSettings::DESPAWN
+                .with_volume(Volume::new(audio.volume(AudioChannel::Music, 1.0))),
+        },
+    ));
+}
+
+/// Background music following the game states, tavern ambience and round stingers
+pub struct MusicPlugin;
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicAssets>()
+            .add_systems(Startup, start_ambience)
+            .add_systems(Update, crossfade_music)
+            .observe(play_stinger);
+    }
+}
@@ -1,18 +1,43 @@
-use bevy::{color::palettes::css::RED, prelude::*};
+use avian3d::prelude::*;
+use std::f32::consts::TAU;
+
+use bevy::{color::palettes::css::RED, prelude::*, utils::HashMap};
 use rand::prelude::*;
+use serde::Deserialize;
 
 use crate::{
-    dice::{Dice, InHandBundle, NewDiceCommand, RollDice, NB_DICES},
+    combination::{Combination, DiceResult},
+    dice::{Cocked, Dice, InHandBundle, NewDiceCommand, RollDice, NB_DICES},
+    game::{NPCRetriesLeft, Round, Side, TurnOver},
     player::PlayerDice,
+    profile::CurrentNPC,
     table::TRAY_RADIUS,
 };
 
 pub const NPC_POSITION: Vec3 = Vec3::new(0.0, TRAY_RADIUS * 1.5, -TRAY_RADIUS * 1.5);
+const NB_FACES: DiceResult = 6;
+const THROW_DELAY: f32 = 0.8;
+const THROW_INTERVAL: f32 = 0.3;
 
+/// Takes a NPC dice in hand, to throw it a bit later
 #[derive(Event)]
 pub struct NPCThrow;
 
+/// Takes a NPC dice in hand, off the tray
+#[derive(Event)]
+pub struct NPCPickup;
+
+/// All NPC dices rest on the table, with their results
+#[derive(Event)]
+pub struct NPCDicesSettled(pub Vec<(Entity, DiceResult)>);
+
+/// Time left before the NPC throws the dice it holds
+#[derive(Component)]
+pub struct ThrowDelay(Timer);
+
 pub fn spawn_npc_dices(mut commands: Commands) {
+    commands.observe(on_npc_dices_settled);
+
     for i in 0..NB_DICES {
         let entity = commands.spawn_empty().id();
 
@@ -24,6 +49,7 @@ pub fn spawn_npc_dices(mut commands: Commands) {
 
         commands
             .entity(entity)
+            .observe(on_npc_pickup)
             .observe(on_npc_throw)
             .insert(Transform::from_xyz(
                 1000.0 + i as f32 * 100.0,
@@ -40,29 +66,241 @@ pub fn roll_npc_dices(
     for entity in &mut q_dices {
         commands.trigger_targets(NPCThrow, entity);
     }
+
+    commands.insert_resource(NPCRetriesLeft::default());
 }
 
-pub fn on_npc_throw(
-    trigger: Trigger<NPCThrow>,
+pub fn on_npc_pickup(
+    trigger: Trigger<NPCPickup>,
     mut commands: Commands,
-    mut q_dices: Query<(&Dice, &mut Transform), Without<PlayerDice>>,
+    mut q_dices: Query<(&Dice, &mut Transform, &mut AngularVelocity), Without<PlayerDice>>,
 ) {
     let entity = trigger.entity();
-    let (dice, mut transform) = q_dices.get_mut(entity).unwrap();
+    let (dice, mut transform, mut angular_velocity) = q_dices.get_mut(entity).unwrap();
+
+    commands
+        .entity(entity)
+        .remove::<Cocked>()
+        .insert(InHandBundle::default());
+    *transform = dice.in_hand_transform(NPC_POSITION);
+
+    // Shake the dice while it is in hand
+    angular_velocity.0 = Vec3::new(1.0, 2.0, 0.5);
+}
+
+pub fn on_npc_throw(trigger: Trigger<NPCThrow>, mut commands: Commands, q_dices: Query<&Dice>) {
+    let entity = trigger.entity();
+    let dice = q_dices.get(entity).unwrap();
+
+    commands.trigger_targets(NPCPickup, entity);
+    commands
+        .entity(entity)
+        .insert(ThrowDelay(Timer::from_seconds(
+            THROW_DELAY + dice.i as f32 * THROW_INTERVAL,
+            TimerMode::Once,
+        )));
+}
 
+pub fn throw_npc_dices_in_hand(
+    mut commands: Commands,
+    mut q_dices: Query<(Entity, &mut ThrowDelay), Without<PlayerDice>>,
+    npc: CurrentNPC,
+    time: Res<Time>,
+) {
     let mut rng = thread_rng();
+    let aim_noise = npc.get().map_or(1.0, |profile| profile.aim_noise);
 
-    commands.entity(entity).insert(InHandBundle::default());
-    *transform = dice.in_hand_transform(NPC_POSITION);
+    for (entity, mut delay) in &mut q_dices {
+        if !delay.0.tick(time.delta()).finished() {
+            continue;
+        }
 
-    commands.trigger_targets(
-        RollDice(Vec3::new(
-            rng.gen_range(-TRAY_RADIUS..=TRAY_RADIUS),
-            0.0,
-            rng.gen_range(-TRAY_RADIUS..=TRAY_RADIUS),
-        )),
-        entity,
-    );
+        // Aim at the middle of the tray, less accurately with more noise
+        let offset = Vec2::from_angle(rng.gen_range(0.0..TAU))
+            * rng.gen_range(0.0..=aim_noise)
+            * TRAY_RADIUS;
+        let power = 0.5 + rng.gen_range(-0.5..=0.5) * aim_noise;
+
+        commands.entity(entity).remove::<ThrowDelay>();
+        commands.trigger_targets(
+            RollDice::Aim {
+                target: Vec3::new(offset.x, 0.0, offset.y),
+                power,
+            },
+            entity,
+        );
+    }
+}
+
+/// Drops the throws still pending when the NPC turn is cut short
+pub fn cancel_npc_throws(mut commands: Commands, q_dices: Query<Entity, With<ThrowDelay>>) {
+    for entity in &q_dices {
+        commands.entity(entity).remove::<ThrowDelay>();
+    }
+}
+
+/// Picks up the dices worth throwing again, or ends the NPC turn
+pub fn on_npc_dices_settled(
+    trigger: Trigger<NPCDicesSettled>,
+    mut commands: Commands,
+    npc: CurrentNPC,
+    round: Res<Round>,
+    mut retries: ResMut<NPCRetriesLeft>,
+) {
+    let dices = &trigger.event().0;
+    let results = dices.iter().map(|(_, result)| *result).collect::<Vec<_>>();
+
+    // Following the player, the NPC only has to beat them
+    let target = match round.leader {
+        Side::Player => round.leader_results.clone().map(Combination::get),
+        Side::Npc => None,
+    };
+
+    let strategy = npc
+        .get()
+        .map_or(Strategy::Greedy, |profile| profile.strategy);
+    let rethrown = strategy.rethrow(&results, retries.0, target.as_ref());
+
+    if rethrown.is_empty() {
+        commands.trigger(TurnOver {
+            side: Side::Npc,
+            results,
+            dices: dices.iter().map(|(entity, _)| *entity).collect(),
+        });
+        return;
+    }
+
+    for i in rethrown {
+        commands.trigger_targets(NPCThrow, dices[i].0);
+        retries.0 -= 1;
+    }
+}
+
+/// How the NPC decides which dices to throw again
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum Strategy {
+    /// Throws dices again on a whim
+    Random,
+    /// Maximizes the expected worth of the next throw
+    Greedy,
+    /// Maximizes the expected final worth, over all the retries left
+    Optimal,
+}
+
+impl Strategy {
+    /// Indices of the dices to throw again, at most `retries` of them.
+    /// With a `target` to beat, stops as soon as it is beaten
+    pub fn rethrow(
+        self,
+        results: &[DiceResult],
+        retries: u8,
+        target: Option<&Combination>,
+    ) -> Vec<usize> {
+        if target.is_some_and(|target| Combination::get(results.to_vec()) > *target) {
+            return vec![];
+        }
+
+        match self {
+            Strategy::Random => {
+                let mut rng = thread_rng();
+                (0..results.len())
+                    .filter(|_| rng.gen_bool(0.5))
+                    .take(retries.into())
+                    .collect()
+            }
+            Strategy::Greedy => {
+                best_rethrow(results, retries, target, &mut |dices, _| {
+                    worth(dices, target)
+                })
+                .1
+            }
+            Strategy::Optimal => {
+                let mut values = HashMap::new();
+                best_rethrow(results, retries, target, &mut |dices, retries_left| {
+                    optimal_value(dices, retries_left, target, &mut values)
+                })
+                .1
+            }
+        }
+    }
+}
+
+/// The score of `results`, or whether they beat the `target` if any
+fn worth(results: &[DiceResult], target: Option<&Combination>) -> f32 {
+    let combination = Combination::get(results.to_vec());
+
+    match target {
+        Some(target) => f32::from(u8::from(combination > *target)),
+        None => combination.score() as f32,
+    }
+}
+
+/// Expected final worth with the best play from `results`, memoized in `values`
+fn optimal_value(
+    results: &[DiceResult],
+    retries: u8,
+    target: Option<&Combination>,
+    values: &mut HashMap<(Vec<DiceResult>, u8), f32>,
+) -> f32 {
+    let mut key = results.to_vec();
+    key.sort_unstable();
+
+    if let Some(value) = values.get(&(key.clone(), retries)) {
+        return *value;
+    }
+
+    let (value, _) = best_rethrow(&key, retries, target, &mut |dices, retries_left| {
+        optimal_value(dices, retries_left, target, values)
+    });
+    values.insert((key, retries), value);
+
+    value
+}
+
+/// Best expected value and the dices to throw again to get it, at most `retries` of them.
+/// `value` gives the worth of the dices after a throw, with the retries left
+fn best_rethrow(
+    results: &[DiceResult],
+    retries: u8,
+    target: Option<&Combination>,
+    value: &mut impl FnMut(&[DiceResult], u8) -> f32,
+) -> (f32, Vec<usize>) {
+    let mut best_value = worth(results, target);
+    let mut best_rethrown = vec![];
+
+    for mask in 1..(1_u32 << results.len()) {
+        if mask.count_ones() > u32::from(retries) {
+            continue;
+        }
+
+        let rethrown = (0..results.len())
+            .filter(|i| mask & (1 << i) != 0)
+            .collect::<Vec<_>>();
+        let retries_left = retries - rethrown.len() as u8;
+
+        // Average over every outcome of the dices thrown again
+        let nb_outcomes = u32::from(NB_FACES).pow(rethrown.len() as u32);
+        let mut dices = results.to_vec();
+        let mut total = 0.0;
+
+        for outcome in 0..nb_outcomes {
+            let mut faces = outcome;
+            for &i in &rethrown {
+                dices[i] = (faces % u32::from(NB_FACES)) as DiceResult + 1;
+                faces /= u32::from(NB_FACES);
+            }
+
+            total += value(&dices, retries_left);
+        }
+
+        let expected = total / nb_outcomes as f32;
+        if expected > best_value {
+            best_value = expected;
+            best_rethrown = rethrown;
+        }
+    }
+
+    (best_value, best_rethrown)
 }
 
 pub fn reroll_fallen_npc_dices(
@@ -75,3 +313,78 @@ pub fn reroll_fallen_npc_dices(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 1e-6, "{value} != {expected}");
+    }
+
+    #[test]
+    fn keeps_the_best_combination() {
+        for strategy in [Strategy::Greedy, Strategy::Optimal] {
+            assert!(strategy.rethrow(&[4, 2, 1], 3, None).is_empty());
+        }
+    }
+
+    #[test]
+    fn keeps_a_good_score_without_target() {
+        // A strike of 6 is worth more than what throwing again can be expected to give
+        for strategy in [Strategy::Greedy, Strategy::Optimal] {
+            assert!(strategy.rethrow(&[6, 6, 6], 3, None).is_empty());
+        }
+    }
+
+    #[test]
+    fn throws_again_a_score_losing_to_the_target() {
+        let target = Combination::get(vec![1, 1, 1]);
+        for strategy in [Strategy::Greedy, Strategy::Optimal] {
+            assert!(!strategy.rethrow(&[6, 6, 6], 3, Some(&target)).is_empty());
+        }
+    }
+
+    #[test]
+    fn stops_once_the_target_is_beaten() {
+        let target = Combination::get(vec![6, 5, 2]);
+        for strategy in [Strategy::Random, Strategy::Greedy, Strategy::Optimal] {
+            assert!(strategy.rethrow(&[2, 2, 2], 3, Some(&target)).is_empty());
+        }
+    }
+
+    #[test]
+    fn optimal_value_without_target_is_the_expected_score() {
+        let mut values = HashMap::new();
+        assert_close(optimal_value(&[4, 2, 1], 3, None, &mut values), 10.0);
+
+        let mut values = HashMap::new();
+        let value = optimal_value(&[6, 5, 2], 0, None, &mut values);
+        assert_close(value, Combination::get(vec![6, 5, 2]).score() as f32);
+    }
+
+    #[test]
+    fn optimal_value_with_target_is_the_chance_to_beat_it() {
+        // Nothing beats a 421
+        let target = Combination::get(vec![4, 2, 1]);
+        let mut values = HashMap::new();
+        assert_close(
+            optimal_value(&[6, 5, 2], 3, Some(&target), &mut values),
+            0.0,
+        );
+
+        let target = Combination::get(vec![6, 5, 2]);
+        let mut values = HashMap::new();
+        assert_close(
+            optimal_value(&[2, 2, 2], 0, Some(&target), &mut values),
+            1.0,
+        );
+
+        // More retries can only help
+        let mut values = HashMap::new();
+        let one_retry = optimal_value(&[6, 5, 2], 1, Some(&target), &mut values);
+        let mut values = HashMap::new();
+        let three_retries = optimal_value(&[6, 5, 2], 3, Some(&target), &mut values);
+        assert!(0.0 < one_retry && one_retry <= three_retries && three_retries < 1.0);
+    }
+}
@@ -1,13 +1,30 @@
 use avian3d::prelude::*;
-use bevy::{color::palettes::css::BLUE, prelude::*};
+use bevy::{
+    color::palettes::css::{BLUE, YELLOW},
+    prelude::*,
+};
+use rand::prelude::*;
 
 use crate::{
-    dice::{Dice, InHand, InHandBundle, NewDiceCommand, RollDice, NB_DICES},
-    game::RetriesLeft,
-    table::{TablePart, TRAY_RADIUS},
+    dice::{
+        aim_velocity, predict_trajectory, Cocked, Dice, InHand, InHandBundle, NewDiceCommand,
+        RollDice, MAX_ANGULAR_SPEED, NB_DICES,
+    },
+    game::{RetriesLeft, RetriesUsed},
+    gamepad::AimReticle,
+    input::{Action, ActionMap, ActionState, Binding},
+    settings::{GameplaySettings, ThrowMode},
+    table::{TablePart, TRAY_RADIUS, TRAY_THICKNESS},
 };
 
 pub const PLAYER_POSITION: Vec3 = Vec3::new(0.0, TRAY_RADIUS * 1.5, TRAY_RADIUS * 1.5);
+const MIN_DRAG_LENGTH: f32 = 20.0;
+const MIN_DRAG_DURATION: f32 = 0.05;
+const DRAG_VELOCITY_FACTOR: f32 = 0.6;
+const MAX_DRAG_VELOCITY: f32 = 30.0;
+/// Upward velocity of a drag throw, relative to the gesture speed
+const DRAG_LIFT_FACTOR: f32 = 0.4;
+const DRAG_SPIN_FACTOR: f32 = 1.5;
 
 #[derive(Component)]
 pub struct PlayerDice;
@@ -15,6 +32,16 @@ pub struct PlayerDice;
 #[derive(Resource)]
 pub struct SelectedDice(pub Entity);
 
+/// Random power of the next aimed throw, rolled ahead so the preview shows the actual throw
+#[derive(Resource)]
+pub struct ThrowPower(pub f32);
+
+impl Default for ThrowPower {
+    fn default() -> Self {
+        Self(thread_rng().gen())
+    }
+}
+
 #[derive(Event)]
 pub struct PickupDice;
 
@@ -55,66 +82,218 @@ pub fn on_pickup_dice(
     let entity = trigger.entity();
     let (dice, mut transform) = q_dices.get_mut(entity).unwrap();
 
-    commands.entity(entity).insert(InHandBundle::default());
+    commands
+        .entity(entity)
+        .insert(InHandBundle::default())
+        .remove::<Cocked>();
     *transform = dice.in_hand_transform(PLAYER_POSITION);
 
     commands.insert_resource(SelectedDice(entity));
 }
 
-#[derive(Component)]
+#[derive(Component, PartialEq, Eq)]
 pub enum ClickType {
     Left,
     Right,
 }
 
+/// A throw being drawn by dragging the pointer
+#[derive(Resource)]
+pub struct DragGesture {
+    start: Vec2,
+    started_at: f32,
+}
+
+impl DragGesture {
+    /// Velocity and spin of a dice thrown by dragging from `start` to `end` on the table
+    fn launch(&self, start: Vec3, end: Vec3, now: f32) -> (Vec3, Vec3) {
+        // The dice follows the hand: direction and speed give the velocity, length gives the spin
+        let gesture = end - start;
+        let duration = (now - self.started_at).max(MIN_DRAG_DURATION);
+
+        let velocity = gesture / duration * DRAG_VELOCITY_FACTOR;
+        // Tossed up like aimed throws, faster gestures throw higher instead of skidding
+        let velocity = (velocity + Vec3::Y * velocity.length() * DRAG_LIFT_FACTOR)
+            .clamp_length_max(MAX_DRAG_VELOCITY);
+        let spin = Vec3::Y.cross(gesture.normalize_or_zero())
+            * (gesture.length() * DRAG_SPIN_FACTOR).min(MAX_ANGULAR_SPEED);
+
+        (velocity, spin)
+    }
+}
+
+/// Position of the touch or mouse cursor on the screen
+pub fn pointer_position(window: &Window, touches: &Touches) -> Option<Vec2> {
+    touches
+        .iter()
+        .chain(touches.iter_just_released())
+        .next()
+        .map(|touch| touch.position())
+        .or_else(|| window.cursor_position())
+}
+
+/// Point of the table plane under a screen position
+pub fn pointer_on_table(
+    (camera, camera_transform): (&Camera, &GlobalTransform),
+    position: Vec2,
+) -> Option<Vec3> {
+    let ray = camera.viewport_to_world(camera_transform, position)?;
+    let distance = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))?;
+    Some(ray.get_point(distance))
+}
+
+fn spawn_click_ray(
+    commands: &mut Commands,
+    (camera, camera_transform): (&Camera, &GlobalTransform),
+    position: Vec2,
+    click_type: ClickType,
+) {
+    // Calculate a ray pointing from the camera into the world based on the cursor's position.
+    if let Some(ray) = camera.viewport_to_world(camera_transform, position) {
+        commands.spawn((RayCaster::from_ray(ray), click_type));
+    }
+}
+
 pub fn click_spawns_raycast(
     mut commands: Commands,
-    button_input: Res<ButtonInput<MouseButton>>,
+    actions: Res<ActionState>,
     touches: Res<Touches>,
     camera_query: Query<(&Camera, &GlobalTransform)>,
     windows: Query<&Window>,
+    q_interactions: Query<&Interaction>,
+    gameplay: Res<GameplaySettings>,
+    time: Res<Time>,
 ) {
-    let cursor_position = if button_input.just_pressed(MouseButton::Left)
-        || button_input.just_pressed(MouseButton::Right)
-    {
-        windows.single().cursor_position()
+    // Clicks on the UI are not meant for the table
+    if q_interactions.iter().any(|i| *i != Interaction::None) {
+        return;
+    }
+
+    let (click_type, binding) = if let Some(binding) = actions.just_pressed_by(Action::Throw) {
+        (ClickType::Left, binding)
+    } else if let Some(binding) = actions.just_pressed_by(Action::PickUp) {
+        (ClickType::Right, binding)
     } else {
-        touches
-            .iter_just_pressed()
-            .next()
-            .map(|touch| touch.position())
+        return;
     };
 
-    let Some(cursor_position) = cursor_position else {
+    // Gamepads aim with the reticle
+    if matches!(binding, Binding::Gamepad(_)) {
+        return;
+    }
+
+    let Some(cursor_position) = pointer_position(windows.single(), &touches) else {
         return;
     };
 
-    let (camera, camera_transform) = camera_query.single();
+    // Throws are drawn until the pointer is released
+    if click_type == ClickType::Left && gameplay.throw_mode == ThrowMode::Drag {
+        commands.insert_resource(DragGesture {
+            start: cursor_position,
+            started_at: time.elapsed_seconds(),
+        });
+        return;
+    }
 
-    // Calculate a ray pointing from the camera into the world based on the cursor's position.
-    let Some(ray) = camera.viewport_to_world(camera_transform, cursor_position) else {
+    spawn_click_ray(
+        &mut commands,
+        camera_query.single(),
+        cursor_position,
+        click_type,
+    );
+}
+
+pub fn release_drag_gesture(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    drag: Option<Res<DragGesture>>,
+    touches: Res<Touches>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    time: Res<Time>,
+    selected_dice: Option<Res<SelectedDice>>,
+    q_dices_in_hand: Query<Entity, (With<PlayerDice>, With<InHand>)>,
+) {
+    let Some(drag) = drag else {
         return;
     };
 
-    commands.spawn((
-        RayCaster::from_ray(ray),
-        if button_input.just_pressed(MouseButton::Right) {
-            ClickType::Right
-        } else {
-            ClickType::Left
-        },
-    ));
+    if !actions.just_released(Action::Throw) {
+        return;
+    }
+
+    commands.remove_resource::<DragGesture>();
+
+    let Some(end) = pointer_position(windows.single(), &touches) else {
+        return;
+    };
+
+    let camera = camera_query.single();
+
+    // Short drags are plain clicks, to select dices
+    if end.distance(drag.start) < MIN_DRAG_LENGTH {
+        spawn_click_ray(&mut commands, camera, end, ClickType::Left);
+        return;
+    }
+
+    let Some(selected_dice) = selected_dice else {
+        return;
+    };
+
+    let (Some(start), Some(end)) = (
+        pointer_on_table(camera, drag.start),
+        pointer_on_table(camera, end),
+    ) else {
+        return;
+    };
+
+    let (velocity, spin) = drag.launch(start, end, time.elapsed_seconds());
+
+    throw_dice(
+        &mut commands,
+        selected_dice.0,
+        RollDice::Launch { velocity, spin },
+        &q_dices_in_hand,
+    );
+}
+
+pub fn select_next_dice(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    q_dices_in_hand: Query<(Entity, &Dice), (With<PlayerDice>, With<InHand>)>,
+    selected_dice: Option<Res<SelectedDice>>,
+) {
+    if !actions.just_pressed(Action::SelectDice) {
+        return;
+    }
+
+    let mut dices = q_dices_in_hand.iter().collect::<Vec<_>>();
+    dices.sort_by_key(|(_, dice)| dice.i);
+
+    let current =
+        selected_dice.and_then(|selected| dices.iter().position(|(e, _)| *e == selected.0));
+
+    let next = match current {
+        Some(i) => dices.get(i + 1).or(dices.first()),
+        None => dices.first(),
+    };
+
+    if let Some((entity, _)) = next {
+        commands.insert_resource(SelectedDice(*entity));
+    }
 }
 
 pub fn raycast_dices(
     mut commands: Commands,
     q_rays: Query<(Entity, &RayCaster, &RayHits, &ClickType)>,
     q_dices_in_hand: Query<Entity, (With<PlayerDice>, With<InHand>)>,
-    q_dices_on_table: Query<Entity, (With<PlayerDice>, Without<InHand>)>,
+    q_dices_on_table: Query<(Entity, Has<Cocked>), (With<PlayerDice>, Without<InHand>)>,
     q_table: Query<(), With<TablePart>>,
     q_children: Query<&Children>,
     selected_dice: Option<Res<SelectedDice>>,
+    power: Res<ThrowPower>,
     mut retries: ResMut<RetriesLeft>,
+    mut retries_used: ResMut<RetriesUsed>,
 ) {
     for (ray_entity, ray, hits, click_type) in &q_rays {
         'hits: for hit in hits.iter_sorted() {
@@ -126,12 +305,17 @@ pub fn raycast_dices(
                 }
             }
 
-            if retries.0 > 0 && matches!(click_type, ClickType::Right) {
-                // Pick up the dices on the table
-                for entity in &q_dices_on_table {
-                    if q_children.iter_descendants(entity).any(|c| c == hit.entity) {
+            if matches!(click_type, ClickType::Right) {
+                // Pick up the dices on the table, cocked dices are free
+                for (entity, cocked) in &q_dices_on_table {
+                    if (retries.0 > 0 || cocked)
+                        && q_children.iter_descendants(entity).any(|c| c == hit.entity)
+                    {
                         commands.trigger_targets(PickupDice, entity);
-                        retries.0 -= 1;
+                        if !cocked {
+                            retries.0 -= 1;
+                            retries_used.0 += 1;
+                        }
                         break 'hits;
                     }
                 }
@@ -142,13 +326,15 @@ pub fn raycast_dices(
                 if let Some(entity) = selected_dice.as_ref().map(|selected_dice| selected_dice.0) {
                     let point = ray.origin + *ray.direction * hit.time_of_impact;
 
-                    commands.trigger_targets(RollDice(point), entity);
-
-                    if let Some(entity) = q_dices_in_hand.iter().find(|e| *e != entity) {
-                        commands.insert_resource(SelectedDice(entity));
-                    } else {
-                        commands.remove_resource::<SelectedDice>();
-                    }
+                    throw_dice(
+                        &mut commands,
+                        entity,
+                        RollDice::Aim {
+                            target: point,
+                            power: power.0,
+                        },
+                        &q_dices_in_hand,
+                    );
 
                     break;
                 }
@@ -159,6 +345,88 @@ pub fn raycast_dices(
     }
 }
 
+/// Throws `entity` and selects another dice in hand, if any
+pub fn throw_dice(
+    commands: &mut Commands,
+    entity: Entity,
+    roll: RollDice,
+    q_dices_in_hand: &Query<Entity, (With<PlayerDice>, With<InHand>)>,
+) {
+    commands.trigger_targets(roll, entity);
+    commands.insert_resource(ThrowPower::default());
+
+    if let Some(entity) = q_dices_in_hand.iter().find(|e| *e != entity) {
+        commands.insert_resource(SelectedDice(entity));
+    } else {
+        commands.remove_resource::<SelectedDice>();
+    }
+}
+
+pub fn preview_throw(
+    mut gizmos: Gizmos,
+    selected_dice: Option<Res<SelectedDice>>,
+    drag: Option<Res<DragGesture>>,
+    gameplay: Res<GameplaySettings>,
+    power: Res<ThrowPower>,
+    action_map: Res<ActionMap>,
+    q_dices_in_hand: Query<&Dice, (With<PlayerDice>, With<InHand>)>,
+    q_reticle: Query<(&Transform, &Visibility), With<AimReticle>>,
+    touches: Res<Touches>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+) {
+    let Some(dice) = selected_dice.and_then(|selected| q_dices_in_hand.get(selected.0).ok()) else {
+        return;
+    };
+
+    let camera = camera_query.single();
+    let from = dice.in_hand_transform(PLAYER_POSITION).translation;
+
+    // Gamepad throws land on the reticle, shown while a gamepad is connected
+    let gamepad_throws = action_map
+        .bindings(Action::Throw)
+        .iter()
+        .any(|binding| matches!(binding, Binding::Gamepad(_)));
+    let reticle = q_reticle
+        .get_single()
+        .ok()
+        .filter(|(_, visibility)| gamepad_throws && **visibility != Visibility::Hidden);
+
+    // Same velocities as the actual throws
+    let velocity = if let Some((reticle, _)) = reticle {
+        aim_velocity(from, reticle.translation.with_y(0.0), power.0, gravity.0)
+    } else {
+        let Some(pointer) = pointer_position(windows.single(), &touches)
+            .and_then(|position| pointer_on_table(camera, position))
+        else {
+            return;
+        };
+
+        match (gameplay.throw_mode, drag) {
+            (ThrowMode::Click, _) => aim_velocity(from, pointer, power.0, gravity.0),
+            (ThrowMode::Drag, Some(drag)) => {
+                let Some(start) = pointer_on_table(camera, drag.start) else {
+                    return;
+                };
+
+                drag.launch(start, pointer, time.elapsed_seconds()).0
+            }
+            (ThrowMode::Drag, None) => return,
+        }
+    };
+
+    let landing_height = TRAY_THICKNESS / 2.0 + dice.size / 2.0;
+    let trajectory = predict_trajectory(from, velocity, gravity.0, landing_height);
+
+    if let Some(landing) = trajectory.last() {
+        gizmos.circle(*landing, Dir3::Y, dice.size, YELLOW);
+    }
+
+    gizmos.linestrip(trajectory, Color::WHITE);
+}
+
 pub fn manage_selected_dice_animation(
     selected_dice: Option<Res<SelectedDice>>,
     mut existed: Local<bool>,
@@ -209,4 +477,42 @@ pub fn pickup_all_player_dices(mut commands: Commands, query: Query<Entity, With
     }
 
     commands.insert_resource(RetriesLeft::default());
+    commands.insert_resource(RetriesUsed::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launch(gesture: Vec3, duration: f32) -> (Vec3, Vec3) {
+        let drag = DragGesture {
+            start: Vec2::ZERO,
+            started_at: 0.0,
+        };
+        drag.launch(Vec3::ZERO, gesture, duration)
+    }
+
+    #[test]
+    fn drag_throws_go_up_along_the_gesture() {
+        let (velocity, spin) = launch(Vec3::new(0.0, 0.0, -4.0), 0.5);
+
+        assert!(velocity.y > 0.0);
+        assert!(velocity.z < 0.0);
+        assert!(velocity.x.abs() < 1e-6);
+        assert!(velocity.length() < MAX_DRAG_VELOCITY);
+        assert!(spin.x < 0.0);
+    }
+
+    #[test]
+    fn drag_velocity_is_clamped() {
+        for gesture in [Vec3::new(0.0, 0.0, -20.0), Vec3::new(50.0, 0.0, 10.0)] {
+            let (velocity, _) = launch(gesture, MIN_DRAG_DURATION);
+
+            assert!((velocity.length() - MAX_DRAG_VELOCITY).abs() < 1e-3);
+            assert!(velocity.y > 0.0);
+        }
+
+        // Shorter than the shortest gesture counts as the shortest
+        assert_eq!(launch(Vec3::X, 0.0).0, launch(Vec3::X, MIN_DRAG_DURATION).0);
+    }
 }
@@ -0,0 +1,289 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AssetPath, AsyncReadExt, LoadContext, LoadState},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    game::GameState,
+    loading::asset_path,
+    npc::Strategy,
+    ui::{spawn_menu, NORMAL_BUTTON},
+};
+
+const PROFILES: [&str; 3] = [
+    "npc/rookie.profile.ron",
+    "npc/gambler.profile.ron",
+    "npc/shark.profile.ron",
+];
+const PORTRAIT_SIZE: f32 = 128.0;
+
+/// An opponent, with its difficulty
+#[derive(Asset, TypePath)]
+pub struct NPCProfile {
+    pub name: String,
+    pub description: String,
+    pub portrait: Handle<Image>,
+    pub strategy: Strategy,
+    /// How far from the middle of the tray the NPC throws may land, from 0 to 1
+    pub aim_noise: f32,
+}
+
+/// The opponent left to play when no profile file could be loaded
+impl Default for NPCProfile {
+    fn default() -> Self {
+        Self {
+            name: "Gambler".into(),
+            description: "Always goes for the best next throw".into(),
+            portrait: Handle::default(),
+            strategy: Strategy::Greedy,
+            aim_noise: 0.4,
+        }
+    }
+}
+
+/// Content of a `.profile.ron` file
+#[derive(Deserialize)]
+struct NPCProfileFile {
+    name: String,
+    description: String,
+    portrait: String,
+    strategy: Strategy,
+    aim_noise: f32,
+}
+
+#[derive(Default)]
+struct NPCProfileLoader;
+
+#[derive(Debug, Error)]
+enum NPCProfileLoaderError {
+    #[error("Could not read the profile: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse the profile: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for NPCProfileLoader {
+    type Asset = NPCProfile;
+    type Settings = ();
+    type Error = NPCProfileLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: NPCProfileFile = ron::de::from_bytes(&bytes)?;
+
+        Ok(NPCProfile {
+            name: file.name,
+            description: file.description,
+            // From where the profile was loaded, the executable or the assets folder
+            portrait: load_context.load(
+                AssetPath::from(file.portrait)
+                    .with_source(load_context.asset_path().source().clone_owned()),
+            ),
+            strategy: file.strategy,
+            aim_noise: file.aim_noise.clamp(0.0, 1.0),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["profile.ron"]
+    }
+}
+
+/// Every opponent that can be picked
+#[derive(Resource)]
+struct NPCProfiles(Vec<Handle<NPCProfile>>);
+
+/// The opponent picked for the run
+#[derive(Resource)]
+pub struct CurrentProfile(pub Handle<NPCProfile>);
+
+/// The profile of the current opponent, once loaded
+#[derive(SystemParam)]
+pub struct CurrentNPC<'w> {
+    current: Option<Res<'w, CurrentProfile>>,
+    profiles: Res<'w, Assets<NPCProfile>>,
+}
+
+impl CurrentNPC<'_> {
+    pub fn get(&self) -> Option<&NPCProfile> {
+        self.profiles.get(&self.current.as_ref()?.0)
+    }
+}
+
+#[derive(Component)]
+struct ProfileMenu;
+
+/// Where the profile cards go, once the profiles are loaded
+#[derive(Component)]
+struct ProfileList;
+
+#[derive(Component)]
+struct ProfileButton(Handle<NPCProfile>);
+
+fn setup_profile_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(NPCProfiles(
+        PROFILES
+            .iter()
+            .map(|path| asset_server.load(asset_path(*path)))
+            .collect(),
+    ));
+
+    spawn_menu(&mut commands, ProfileMenu, Display::None, |c| {
+        c.spawn(TextBundle::from_section(
+            "Choose your opponent",
+            TextStyle {
+                font_size: 50.0,
+                ..default()
+            },
+        ));
+
+        c.spawn((
+            ProfileList,
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(20.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    });
+}
+
+fn fill_profile_list(
+    mut commands: Commands,
+    mut filled: Local<bool>,
+    asset_server: Res<AssetServer>,
+    mut npc_profiles: ResMut<NPCProfiles>,
+    mut profiles: ResMut<Assets<NPCProfile>>,
+    q_list: Query<Entity, With<ProfileList>>,
+) {
+    if *filled {
+        return;
+    }
+
+    // Wait for every profile, skipping the ones that failed to load
+    let done = npc_profiles.0.iter().all(|handle| {
+        profiles.contains(handle)
+            || matches!(
+                asset_server.get_load_state(handle),
+                Some(LoadState::Failed(_))
+            )
+    });
+    if !done {
+        return;
+    }
+    *filled = true;
+
+    // The menu would have no way out
+    if !npc_profiles
+        .0
+        .iter()
+        .any(|handle| profiles.contains(handle))
+    {
+        warn!("No NPC profile could be loaded, using the default one");
+        let handle = profiles.add(NPCProfile::default());
+        npc_profiles.0.push(handle);
+    }
+
+    commands.entity(q_list.single()).with_children(|c| {
+        for handle in &npc_profiles.0 {
+            let Some(profile) = profiles.get(handle) else {
+                continue;
+            };
+
+            c.spawn((
+                ProfileButton(handle.clone()),
+                ButtonBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        width: Val::Px(PORTRAIT_SIZE * 2.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        border: UiRect::all(Val::Px(2.0)),
+                        row_gap: Val::Px(5.0),
+                        ..default()
+                    },
+                    border_color: BorderColor(Color::WHITE),
+                    background_color: NORMAL_BUTTON.into(),
+                    ..default()
+                },
+            ))
+            .with_children(|c| {
+                c.spawn(ImageBundle {
+                    style: Style {
+                        width: Val::Px(PORTRAIT_SIZE),
+                        height: Val::Px(PORTRAIT_SIZE),
+                        ..default()
+                    },
+                    image: UiImage::new(profile.portrait.clone()),
+                    ..default()
+                });
+                c.spawn(TextBundle::from_section(
+                    &profile.name,
+                    TextStyle {
+                        font_size: 40.0,
+                        ..default()
+                    },
+                ));
+                c.spawn(
+                    TextBundle::from_section(
+                        &profile.description,
+                        TextStyle {
+                            font_size: 20.0,
+                            ..default()
+                        },
+                    )
+                    .with_text_justify(JustifyText::Center),
+                );
+            });
+        }
+    });
+}
+
+fn update_profile_buttons(
+    mut commands: Commands,
+    q_btn: Query<(&Interaction, &ProfileButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, button) in &q_btn {
+        if *interaction == Interaction::Pressed {
+            commands.insert_resource(CurrentProfile(button.0.clone()));
+            next_state.set(GameState::RoundStart);
+        }
+    }
+}
+
+fn show_profile_menu(mut q_menu: Query<&mut Style, With<ProfileMenu>>) {
+    q_menu.single_mut().display = Display::Flex;
+}
+
+fn hide_profile_menu(mut q_menu: Query<&mut Style, With<ProfileMenu>>) {
+    q_menu.single_mut().display = Display::None;
+}
+
+/// Opponents loaded from `.profile.ron` files, picked before a run
+pub struct ProfilePlugin;
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<NPCProfile>()
+            .init_asset_loader::<NPCProfileLoader>()
+            .add_systems(Startup, setup_profile_menu)
+            .add_systems(
+                Update,
+                (fill_profile_list, update_profile_buttons).run_if(in_state(GameState::Setup)),
+            )
+            .add_systems(OnEnter(GameState::Setup), show_profile_menu)
+            .add_systems(OnExit(GameState::Setup), hide_profile_menu);
+    }
+}
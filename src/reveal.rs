@@ -0,0 +1,235 @@
+use bevy::{
+    color::palettes::css::{GOLD, GREEN, ORANGE, RED, YELLOW},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use rand::prelude::*;
+
+use crate::{
+    game::{GameState, Outcome},
+    input::{Action, ActionState},
+    ui::{DisplayScore, OutcomeTitle},
+};
+
+/// Time for the combination to pop in, in seconds
+const POP_TIME: f32 = 0.5;
+/// Time for the payout to count up once the combination is shown, in seconds
+const COUNT_TIME: f32 = 1.0;
+const COMBINATION_FONT_SIZE: f32 = 70.0;
+/// Score from which a winning combination gets a particle burst, like 421 or three 1
+const BIG_COMBINATION_SCORE: u32 = 7;
+const NB_PARTICLES: usize = 80;
+const PARTICLE_SIZE: f32 = 10.0;
+const PARTICLE_SPEED: f32 = 600.0;
+const PARTICLE_LIFETIME: f32 = 1.5;
+/// Downwards acceleration of the particles, in pixels per second squared
+const PARTICLE_GRAVITY: f32 = 900.0;
+const MUTED_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+
+/// The combination deciding the round, popping in
+#[derive(Component)]
+pub struct RevealedCombination;
+
+/// Money won or lost in the round, counting up
+#[derive(Component)]
+pub struct RevealedPayout;
+
+/// The outcome of the round being revealed, removed once done or skipped
+#[derive(Resource)]
+pub struct Reveal {
+    elapsed: f32,
+    payout: i32,
+    /// A lost round, revealed without fanfare
+    muted: bool,
+}
+
+#[derive(Component)]
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    age: f32,
+}
+
+fn start_reveal(
+    trigger: Trigger<DisplayScore>,
+    mut commands: Commands,
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_title: Query<&mut Text, With<OutcomeTitle>>,
+    mut q_combination: Query<&mut Text, (With<RevealedCombination>, Without<OutcomeTitle>)>,
+    mut q_payout: Query<
+        &mut Text,
+        (
+            With<RevealedPayout>,
+            Without<RevealedCombination>,
+            Without<OutcomeTitle>,
+        ),
+    >,
+) {
+    let DisplayScore::Result {
+        npc,
+        player,
+        outcome,
+        payout,
+        ..
+    } = trigger.event()
+    else {
+        return;
+    };
+
+    let muted = *outcome == Outcome::Lose;
+    let (deciding, color, payout_color) = match outcome {
+        Outcome::Win => (player, GOLD.into(), GREEN.into()),
+        Outcome::Lose => (npc, MUTED_COLOR, RED.with_alpha(0.6).into()),
+        Outcome::Push => (player, Color::WHITE, Color::WHITE),
+    };
+
+    q_title.single_mut().sections[0].style.color = color;
+
+    let mut combination = q_combination.single_mut();
+    combination.sections[0].value = deciding.combination.name();
+    combination.sections[0].style.color = color;
+
+    let mut payout_text = q_payout.single_mut();
+    payout_text.sections[0].value = match outcome {
+        Outcome::Push => "Wager returned".into(),
+        _ => "0¤".into(),
+    };
+    payout_text.sections[0].style.color = payout_color;
+
+    commands.insert_resource(Reveal {
+        elapsed: 0.0,
+        payout: *payout,
+        muted,
+    });
+
+    if *outcome == Outcome::Win && player.combination.score() >= BIG_COMBINATION_SCORE {
+        let center = primary_window
+            .get_single()
+            .map_or(Vec2::ZERO, |window| window.size() / 2.0);
+        spawn_particles(&mut commands, center);
+    }
+}
+
+fn spawn_particles(commands: &mut Commands, center: Vec2) {
+    let mut rng = thread_rng();
+    let colors = [GOLD, YELLOW, ORANGE];
+
+    for _ in 0..NB_PARTICLES {
+        let direction = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+        let velocity = direction * PARTICLE_SPEED * rng.gen_range(0.3..1.0);
+
+        commands.spawn((
+            Particle {
+                position: center,
+                velocity,
+                age: 0.0,
+            },
+            StateScoped(GameState::Resolution),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(center.x),
+                    top: Val::Px(center.y),
+                    width: Val::Px(PARTICLE_SIZE),
+                    height: Val::Px(PARTICLE_SIZE),
+                    ..default()
+                },
+                background_color: Color::from(*colors.choose(&mut rng).unwrap()).into(),
+                // Over the resolution panel
+                z_index: ZIndex::Global(1),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn animate_reveal(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut reveal: ResMut<Reveal>,
+    mut q_combination: Query<&mut Text, With<RevealedCombination>>,
+    mut q_payout: Query<&mut Text, (With<RevealedPayout>, Without<RevealedCombination>)>,
+) {
+    reveal.elapsed += time.delta_seconds();
+
+    // Wins overshoot before settling, losses just fade in
+    let pop = (reveal.elapsed / POP_TIME).min(1.0);
+    let mut combination = q_combination.single_mut();
+    let section = &mut combination.sections[0];
+    if reveal.muted {
+        section.style.font_size = COMBINATION_FONT_SIZE;
+        section.style.color.set_alpha(pop);
+    } else {
+        section.style.font_size = COMBINATION_FONT_SIZE * ease_out_back(pop).max(0.05);
+    }
+
+    let count = ((reveal.elapsed - POP_TIME) / COUNT_TIME).clamp(0.0, 1.0);
+    if reveal.payout != 0 {
+        let shown = (reveal.payout as f32 * count).round() as i32;
+        q_payout.single_mut().sections[0].value = format!("{shown:+}¤");
+    }
+
+    if count >= 1.0 {
+        commands.remove_resource::<Reveal>();
+    }
+}
+
+/// Goes from 0 to 1, a bit over 1 before the end
+fn ease_out_back(t: f32) -> f32 {
+    const OVERSHOOT: f32 = 1.7;
+    let t = t - 1.0;
+    1.0 + t * t * ((OVERSHOOT + 1.0) * t + OVERSHOOT)
+}
+
+fn skip_reveal(actions: Res<ActionState>, mut reveal: ResMut<Reveal>) {
+    if actions.just_pressed(Action::Throw) || actions.just_pressed(Action::SkipTurn) {
+        reveal.elapsed = POP_TIME + COUNT_TIME;
+    }
+}
+
+fn move_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut q_particles: Query<(Entity, &mut Particle, &mut Style, &mut BackgroundColor)>,
+) {
+    let delta = time.delta_seconds();
+
+    for (entity, mut particle, mut style, mut color) in &mut q_particles {
+        particle.age += delta;
+        if particle.age >= PARTICLE_LIFETIME {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        particle.velocity.y += PARTICLE_GRAVITY * delta;
+        let velocity = particle.velocity;
+        particle.position += velocity * delta;
+
+        style.left = Val::Px(particle.position.x);
+        style.top = Val::Px(particle.position.y);
+        color.0.set_alpha(1.0 - particle.age / PARTICLE_LIFETIME);
+    }
+}
+
+fn clear_reveal(mut commands: Commands) {
+    commands.remove_resource::<Reveal>();
+}
+
+/// Animated outcome of a round, with particles for the best combinations
+pub struct RevealPlugin;
+impl Plugin for RevealPlugin {
+    fn build(&self, app: &mut App) {
+        app.observe(start_reveal)
+            .add_systems(
+                Update,
+                (
+                    (skip_reveal, animate_reveal)
+                        .chain()
+                        .run_if(resource_exists::<Reveal>),
+                    move_particles,
+                )
+                    .run_if(in_state(GameState::Resolution)),
+            )
+            .add_systems(OnExit(GameState::Resolution), clear_reveal);
+    }
+}
@@ -0,0 +1,839 @@
+use bevy::{
+    color::palettes::css::RED, ecs::system::SystemParam, prelude::*, window::PrimaryWindow,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    flycam::MovementSettings,
+    input::{Action, ActionMap, Rebinding},
+    player::pointer_position,
+    storage,
+    ui::{spawn_button, spawn_menu, OpenOverlay, Overlay, OVERLAY_Z_INDEX},
+};
+
+const SETTINGS_KEY: &str = "settings";
+const SETTINGS_Z_INDEX: i32 = OVERLAY_Z_INDEX + 5;
+const SLIDER_WIDTH: f32 = 200.0;
+const SLIDER_HEIGHT: f32 = 12.0;
+const SLIDER_COLOR: Color = Color::srgb(0.3, 0.3, 0.3);
+const SLIDER_FILL_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+
+/// Volume levels, from 0 to 1
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 0.6,
+            sfx: 1.0,
+        }
+    }
+}
+
+/// Kinds of sounds with their own volume
+#[derive(Clone, Copy)]
+pub enum AudioChannel {
+    Music,
+    Sfx,
+}
+
+impl AudioSettings {
+    /// Volume of a new sound of `channel` at `level`, the global volume applies on top of it
+    pub fn volume(&self, channel: AudioChannel, level: f32) -> f32 {
+        let channel = match channel {
+            AudioChannel::Music => self.music,
+            AudioChannel::Sfx => self.sfx,
+        };
+
+        channel * level
+    }
+
+    /// Volume to set on the sink of a playing sound, which ignores the global volume
+    pub fn sink_volume(&self, channel: AudioChannel, level: f32) -> f32 {
+        self.master * self.volume(channel, level)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ThrowMode {
+    /// Click the table to throw with a random strength
+    #[default]
+    Click,
+    /// Drag the pointer to choose the strength and spin
+    Drag,
+}
+
+/// What happens to a dice resting tilted on the table
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CockedDiceRule {
+    /// The dice is thrown again, for free
+    #[default]
+    Rethrow,
+    /// The dice counts as a 1
+    CountsAsLowest,
+    /// The dice must be picked up, for free
+    PickUp,
+}
+
+/// What happens to a dice that still has not settled after a while
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StuckDiceRecovery {
+    /// Give the dice a small push
+    Nudge,
+    /// Drop the dice again on the middle of the tray
+    Rethrow,
+    /// Lay the dice flat on the face pointing up
+    #[default]
+    SnapToFace,
+}
+
+impl StuckDiceRecovery {
+    pub fn label(self) -> &'static str {
+        match self {
+            StuckDiceRecovery::Nudge => "Nudge",
+            StuckDiceRecovery::Rethrow => "Re-throw",
+            StuckDiceRecovery::SnapToFace => "Snap to face",
+        }
+    }
+}
+
+/// Who throws first in each round, the other side then has to beat that
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TurnOrder {
+    #[default]
+    NPCFirst,
+    PlayerFirst,
+    /// The loser of the previous round leads the next one
+    LoserLeads,
+}
+
+/// Who wins when both sides get the same combination
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TiePolicy {
+    #[default]
+    PlayerWins,
+    HouseWins,
+    /// Nobody wins, the wager is returned
+    Push,
+    /// Each side throws a single dice, the highest wins
+    SuddenDeath,
+}
+
+/// When a side wins the match
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum MatchFormat {
+    /// Winning the most of this many rounds
+    BestOf(u8),
+    /// Scoring this many points, the value of the winning combinations
+    FirstTo(u32),
+}
+
+impl Default for MatchFormat {
+    fn default() -> Self {
+        Self::BestOf(3)
+    }
+}
+
+impl MatchFormat {
+    pub const ALL: [Self; 5] = [
+        Self::BestOf(3),
+        Self::BestOf(5),
+        Self::BestOf(7),
+        Self::FirstTo(10),
+        Self::FirstTo(21),
+    ];
+
+    pub fn label(self) -> String {
+        match self {
+            MatchFormat::BestOf(rounds) => format!("Best of {rounds}"),
+            MatchFormat::FirstTo(points) => format!("First to {points}¤"),
+        }
+    }
+}
+
+/// Rules and gameplay preferences
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameplaySettings {
+    pub throw_mode: ThrowMode,
+    pub cocked_dice: CockedDiceRule,
+    pub stuck_dice: StuckDiceRecovery,
+    pub turn_order: TurnOrder,
+    pub tie_policy: TiePolicy,
+    pub match_format: MatchFormat,
+    /// Values of the settled dices written above them
+    pub face_labels: bool,
+}
+
+/// Content of the settings file
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    movement: MovementSettings,
+    actions: ActionMap,
+    audio: AudioSettings,
+    gameplay: GameplaySettings,
+}
+
+/// Every resource saved in the settings file
+#[derive(SystemParam)]
+struct SettingsResources<'w> {
+    movement: ResMut<'w, MovementSettings>,
+    actions: ResMut<'w, ActionMap>,
+    audio: ResMut<'w, AudioSettings>,
+    gameplay: ResMut<'w, GameplaySettings>,
+}
+
+/// Read only access to [`SettingsResources`], for the systems showing or saving them
+#[derive(SystemParam)]
+struct SettingsValues<'w> {
+    movement: Res<'w, MovementSettings>,
+    actions: Res<'w, ActionMap>,
+    audio: Res<'w, AudioSettings>,
+    gameplay: Res<'w, GameplaySettings>,
+}
+
+impl SettingsValues<'_> {
+    fn is_added(&self) -> bool {
+        self.movement.is_added()
+            || self.actions.is_added()
+            || self.audio.is_added()
+            || self.gameplay.is_added()
+    }
+
+    fn is_changed(&self) -> bool {
+        self.movement.is_changed()
+            || self.actions.is_changed()
+            || self.audio.is_changed()
+            || self.gameplay.is_changed()
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+enum Setting {
+    Sensitivity,
+    Speed,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    ThrowMode,
+    CockedDice,
+    StuckDice,
+    TurnOrder,
+    TiePolicy,
+    MatchFormat,
+    FaceLabels,
+}
+
+impl Setting {
+    const ALL: [Self; 12] = [
+        Self::Sensitivity,
+        Self::Speed,
+        Self::MasterVolume,
+        Self::MusicVolume,
+        Self::SfxVolume,
+        Self::ThrowMode,
+        Self::CockedDice,
+        Self::StuckDice,
+        Self::TurnOrder,
+        Self::TiePolicy,
+        Self::MatchFormat,
+        Self::FaceLabels,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Setting::Sensitivity => "Mouse sensitivity",
+            Setting::Speed => "Camera speed",
+            Setting::MasterVolume => "Master volume",
+            Setting::MusicVolume => "Music volume",
+            Setting::SfxVolume => "Effects volume",
+            Setting::ThrowMode => "Throw",
+            Setting::CockedDice => "Cocked dice",
+            Setting::StuckDice => "Stuck dice",
+            Setting::TurnOrder => "First to throw",
+            Setting::TiePolicy => "On a tie",
+            Setting::MatchFormat => "Match",
+            Setting::FaceLabels => "Dice values",
+        }
+    }
+
+    fn value(self, settings: &SettingsValues) -> String {
+        match self {
+            Setting::Sensitivity => format!("{:.0}", settings.movement.sensitivity * 100_000.0),
+            Setting::Speed => format!("{:.0}", settings.movement.speed),
+            Setting::MasterVolume => format!("{:.0}%", settings.audio.master * 100.0),
+            Setting::MusicVolume => format!("{:.0}%", settings.audio.music * 100.0),
+            Setting::SfxVolume => format!("{:.0}%", settings.audio.sfx * 100.0),
+            Setting::ThrowMode => match settings.gameplay.throw_mode {
+                ThrowMode::Click => "Click".into(),
+                ThrowMode::Drag => "Drag".into(),
+            },
+            Setting::CockedDice => match settings.gameplay.cocked_dice {
+                CockedDiceRule::Rethrow => "Re-throw".into(),
+                CockedDiceRule::CountsAsLowest => "Counts as 1".into(),
+                CockedDiceRule::PickUp => "Pick up".into(),
+            },
+            Setting::StuckDice => settings.gameplay.stuck_dice.label().into(),
+            Setting::TurnOrder => match settings.gameplay.turn_order {
+                TurnOrder::NPCFirst => "NPC".into(),
+                TurnOrder::PlayerFirst => "Player".into(),
+                TurnOrder::LoserLeads => "Last loser".into(),
+            },
+            Setting::TiePolicy => match settings.gameplay.tie_policy {
+                TiePolicy::PlayerWins => "Player wins".into(),
+                TiePolicy::HouseWins => "House wins".into(),
+                TiePolicy::Push => "Push".into(),
+                TiePolicy::SuddenDeath => "Sudden death".into(),
+            },
+            Setting::MatchFormat => settings.gameplay.match_format.label(),
+            Setting::FaceLabels => if settings.gameplay.face_labels {
+                "Shown"
+            } else {
+                "Hidden"
+            }
+            .into(),
+        }
+    }
+
+    fn is_volume(self) -> bool {
+        matches!(
+            self,
+            Setting::MasterVolume | Setting::MusicVolume | Setting::SfxVolume
+        )
+    }
+
+    /// Level of the volume settings, from 0 to 1
+    fn volume(self, audio: &AudioSettings) -> Option<f32> {
+        match self {
+            Setting::MasterVolume => Some(audio.master),
+            Setting::MusicVolume => Some(audio.music),
+            Setting::SfxVolume => Some(audio.sfx),
+            _ => None,
+        }
+    }
+
+    fn set_volume(self, audio: &mut AudioSettings, level: f32) {
+        let level = level.clamp(0.0, 1.0);
+        match self {
+            Setting::MasterVolume => audio.master = level,
+            Setting::MusicVolume => audio.music = level,
+            Setting::SfxVolume => audio.sfx = level,
+            _ => {}
+        }
+    }
+
+    fn adjust(self, settings: &mut SettingsResources, step: f32) {
+        match self {
+            Setting::Sensitivity => {
+                let movement = &mut settings.movement;
+                movement.sensitivity =
+                    (movement.sensitivity + step * 0.00002).clamp(0.00002, 0.0005);
+            }
+            Setting::Speed => {
+                let movement = &mut settings.movement;
+                movement.speed = (movement.speed + step * 2.0).clamp(2.0, 40.0);
+            }
+            Setting::MasterVolume | Setting::MusicVolume | Setting::SfxVolume => {
+                let level = self.volume(&settings.audio).unwrap_or_default();
+                self.set_volume(&mut settings.audio, level + step * 0.1);
+            }
+            Setting::ThrowMode => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.throw_mode = cycle(
+                    &[ThrowMode::Click, ThrowMode::Drag],
+                    gameplay.throw_mode,
+                    step,
+                );
+            }
+            Setting::CockedDice => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.cocked_dice = cycle(
+                    &[
+                        CockedDiceRule::Rethrow,
+                        CockedDiceRule::CountsAsLowest,
+                        CockedDiceRule::PickUp,
+                    ],
+                    gameplay.cocked_dice,
+                    step,
+                );
+            }
+            Setting::StuckDice => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.stuck_dice = cycle(
+                    &[
+                        StuckDiceRecovery::Nudge,
+                        StuckDiceRecovery::Rethrow,
+                        StuckDiceRecovery::SnapToFace,
+                    ],
+                    gameplay.stuck_dice,
+                    step,
+                );
+            }
+            Setting::TurnOrder => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.turn_order = cycle(
+                    &[
+                        TurnOrder::NPCFirst,
+                        TurnOrder::PlayerFirst,
+                        TurnOrder::LoserLeads,
+                    ],
+                    gameplay.turn_order,
+                    step,
+                );
+            }
+            Setting::TiePolicy => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.tie_policy = cycle(
+                    &[
+                        TiePolicy::PlayerWins,
+                        TiePolicy::HouseWins,
+                        TiePolicy::Push,
+                        TiePolicy::SuddenDeath,
+                    ],
+                    gameplay.tie_policy,
+                    step,
+                );
+            }
+            Setting::MatchFormat => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.match_format = cycle(&MatchFormat::ALL, gameplay.match_format, step);
+            }
+            Setting::FaceLabels => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.face_labels = !gameplay.face_labels;
+            }
+        }
+    }
+}
+
+/// The value before or after `current` in `values`, depending on the sign of `step`
+fn cycle<T: Copy + PartialEq>(values: &[T], current: T, step: f32) -> T {
+    let i = values
+        .iter()
+        .position(|v| *v == current)
+        .unwrap_or_default();
+    let len = values.len();
+
+    if step < 0.0 {
+        values[(i + len - 1) % len]
+    } else {
+        values[(i + 1) % len]
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum SettingsPanel {
+    Settings,
+    Controls,
+}
+
+#[derive(Component)]
+enum SettingsButton {
+    Toggle,
+    Open(SettingsPanel),
+    Adjust(Setting, f32),
+    Rebind(Action),
+    ResetControls,
+}
+
+#[derive(Component)]
+struct SettingValueText(Setting);
+
+/// Track of a volume slider, set to the level under the pointer while pressed
+#[derive(Component)]
+struct VolumeSlider(Setting);
+
+/// Part of a volume slider filled up to the level
+#[derive(Component)]
+struct SliderFill(Setting);
+
+#[derive(Component)]
+struct BindingsText(Action);
+
+fn setup_settings_menu(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|c| {
+            spawn_button(c, "Settings", 30.0, SettingsButton::Toggle);
+        });
+
+    // Above the other menus, which can open it
+    let settings_panel = spawn_menu(&mut commands, SettingsPanel::Settings, Display::None, |c| {
+        for setting in Setting::ALL {
+            c.spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(10.0),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|c| {
+                spawn_label(c, setting.label());
+
+                if setting.is_volume() {
+                    spawn_slider(c, setting);
+                } else {
+                    spawn_button(c, "-", 30.0, SettingsButton::Adjust(setting, -1.0));
+                }
+
+                c.spawn((
+                    SettingValueText(setting),
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 30.0,
+                            ..default()
+                        },
+                    ),
+                ));
+
+                if !setting.is_volume() {
+                    spawn_button(c, "+", 30.0, SettingsButton::Adjust(setting, 1.0));
+                }
+            });
+        }
+
+        spawn_button(
+            c,
+            "Controls",
+            30.0,
+            SettingsButton::Open(SettingsPanel::Controls),
+        );
+        spawn_button(c, "Close", 30.0, SettingsButton::Toggle);
+    });
+
+    commands
+        .entity(settings_panel)
+        .insert(ZIndex::Global(SETTINGS_Z_INDEX));
+
+    let controls_panel = spawn_menu(&mut commands, SettingsPanel::Controls, Display::None, |c| {
+        for action in Action::ALL {
+            c.spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(10.0),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|c| {
+                spawn_label(c, action.label());
+
+                c.spawn((
+                    BindingsText(action),
+                    TextBundle {
+                        style: Style {
+                            width: Val::Px(400.0),
+                            ..default()
+                        },
+                        text: Text::from_section(
+                            "",
+                            TextStyle {
+                                font_size: 24.0,
+                                ..default()
+                            },
+                        ),
+                        ..default()
+                    },
+                ));
+
+                spawn_button(c, "Rebind", 24.0, SettingsButton::Rebind(action));
+            });
+        }
+
+        c.spawn(NodeBundle {
+            style: Style {
+                column_gap: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|c| {
+            spawn_button(c, "Reset", 30.0, SettingsButton::ResetControls);
+            spawn_button(
+                c,
+                "Back",
+                30.0,
+                SettingsButton::Open(SettingsPanel::Settings),
+            );
+        });
+    });
+
+    commands
+        .entity(controls_panel)
+        .insert(ZIndex::Global(SETTINGS_Z_INDEX));
+}
+
+fn spawn_slider(parent: &mut ChildBuilder, setting: Setting) {
+    parent
+        .spawn((
+            VolumeSlider(setting),
+            Interaction::default(),
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(SLIDER_WIDTH),
+                    height: Val::Px(SLIDER_HEIGHT),
+                    ..default()
+                },
+                background_color: SLIDER_COLOR.into(),
+                ..default()
+            },
+        ))
+        .with_children(|c| {
+            c.spawn((
+                SliderFill(setting),
+                NodeBundle {
+                    style: Style {
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: SLIDER_FILL_COLOR.into(),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn spawn_label(parent: &mut ChildBuilder, label: &str) {
+    parent.spawn(TextBundle {
+        style: Style {
+            width: Val::Px(300.0),
+            ..default()
+        },
+        text: Text::from_section(
+            label,
+            TextStyle {
+                font_size: 30.0,
+                ..default()
+            },
+        ),
+        ..default()
+    });
+}
+
+fn open_settings(
+    trigger: Trigger<OpenOverlay>,
+    mut commands: Commands,
+    mut q_panels: Query<(&mut Style, &SettingsPanel)>,
+) {
+    if trigger.event().0 != Overlay::Settings {
+        return;
+    }
+
+    for (mut style, panel) in &mut q_panels {
+        style.display = if *panel == SettingsPanel::Settings {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+
+    commands.remove_resource::<Rebinding>();
+}
+
+fn update_settings_buttons(
+    mut commands: Commands,
+    q_btn: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+    mut q_panels: Query<(&mut Style, &SettingsPanel)>,
+    mut settings: SettingsResources,
+) {
+    for (interaction, button) in &q_btn {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            SettingsButton::Toggle => {
+                let open = q_panels
+                    .iter()
+                    .any(|(style, _)| style.display != Display::None);
+
+                for (mut style, panel) in &mut q_panels {
+                    style.display = if !open && *panel == SettingsPanel::Settings {
+                        Display::Flex
+                    } else {
+                        Display::None
+                    };
+                }
+
+                commands.remove_resource::<Rebinding>();
+            }
+            SettingsButton::Open(open_panel) => {
+                for (mut style, panel) in &mut q_panels {
+                    style.display = if panel == open_panel {
+                        Display::Flex
+                    } else {
+                        Display::None
+                    };
+                }
+
+                commands.remove_resource::<Rebinding>();
+            }
+            SettingsButton::Adjust(setting, step) => {
+                setting.adjust(&mut settings, *step);
+            }
+            SettingsButton::Rebind(action) => {
+                commands.insert_resource(Rebinding(*action));
+            }
+            SettingsButton::ResetControls => {
+                *settings.actions = ActionMap::default();
+            }
+        }
+    }
+}
+
+fn drag_volume_sliders(
+    q_sliders: Query<(&Interaction, &VolumeSlider, &Node, &GlobalTransform)>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    touches: Res<Touches>,
+    mut audio: ResMut<AudioSettings>,
+) {
+    let Some(pointer) = q_window
+        .get_single()
+        .ok()
+        .and_then(|window| pointer_position(window, &touches))
+    else {
+        return;
+    };
+
+    // Only changed levels are saved, not each frame of a drag
+    let mut levels = audio.clone();
+    for (interaction, slider, node, transform) in &q_sliders {
+        if *interaction == Interaction::Pressed {
+            let rect = node.logical_rect(transform);
+            slider
+                .0
+                .set_volume(&mut levels, (pointer.x - rect.min.x) / rect.width());
+        }
+    }
+    audio.set_if_neq(levels);
+}
+
+fn update_bindings_texts(
+    mut query: Query<(&mut Text, &BindingsText)>,
+    action_map: Res<ActionMap>,
+    rebinding: Option<Res<Rebinding>>,
+    mut was_rebinding: Local<bool>,
+) {
+    let rebinding_changed =
+        rebinding.as_ref().is_some_and(|r| r.is_changed()) || rebinding.is_some() != *was_rebinding;
+    *was_rebinding = rebinding.is_some();
+
+    if !action_map.is_changed() && !rebinding_changed {
+        return;
+    }
+
+    for (mut text, BindingsText(action)) in &mut query {
+        let section = &mut text.sections[0];
+
+        if rebinding.as_ref().is_some_and(|r| r.0 == *action) {
+            section.value = "Press a key or button, Escape to cancel...".into();
+            section.style.color = Color::WHITE;
+            continue;
+        }
+
+        let bindings = action_map
+            .bindings(*action)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let conflicts = action_map.conflicts(*action);
+
+        if conflicts.is_empty() {
+            section.value = bindings;
+            section.style.color = Color::WHITE;
+        } else {
+            let conflicts = conflicts
+                .iter()
+                .map(|a| a.label())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            section.value = format!("{bindings} (conflicts with {conflicts})");
+            section.style.color = RED.into();
+        }
+    }
+}
+
+fn update_setting_values(
+    mut query: Query<(&mut Text, &SettingValueText)>,
+    mut q_fills: Query<(&mut Style, &SliderFill)>,
+    settings: SettingsValues,
+) {
+    if settings.is_changed() {
+        for (mut text, value) in &mut query {
+            text.sections[0].value = value.0.value(&settings);
+        }
+
+        for (mut style, fill) in &mut q_fills {
+            let level = fill.0.volume(&settings.audio).unwrap_or_default();
+            style.width = Val::Percent(level * 100.0);
+        }
+    }
+}
+
+fn apply_audio_settings(mut commands: Commands, audio: Res<AudioSettings>) {
+    if audio.is_changed() {
+        commands.insert_resource(GlobalVolume::new(audio.master));
+    }
+}
+
+fn save_settings(settings: SettingsValues) {
+    if settings.is_changed() && !settings.is_added() {
+        storage::save(
+            SETTINGS_KEY,
+            &Settings {
+                movement: settings.movement.clone(),
+                actions: settings.actions.clone(),
+                audio: settings.audio.clone(),
+                gameplay: settings.gameplay.clone(),
+            },
+        );
+    }
+}
+
+/// Loads the saved settings at startup and saves them whenever they change
+pub struct SettingsPlugin;
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let mut settings = storage::load::<Settings>(SETTINGS_KEY).unwrap_or_default();
+        settings.actions.fill_missing();
+
+        app.insert_resource(settings.movement)
+            .insert_resource(settings.actions)
+            .insert_resource(settings.audio)
+            .insert_resource(settings.gameplay)
+            .observe(open_settings)
+            .add_systems(Startup, setup_settings_menu)
+            .add_systems(
+                Update,
+                (
+                    update_settings_buttons,
+                    drag_volume_sliders,
+                    update_setting_values,
+                    update_bindings_texts,
+                    apply_audio_settings,
+                    save_settings,
+                ),
+            );
+    }
+}
@@ -0,0 +1,256 @@
+use avian3d::prelude::*;
+use bevy::{audio::Volume, ecs::system::SystemParam, prelude::*};
+use rand::prelude::*;
+
+use crate::{
+    dice::{Dice, RollDice},
+    loading::asset_path,
+    npc::NPCPickup,
+    player::PickupDice,
+    settings::{AudioChannel, AudioSettings},
+    table::TablePart,
+};
+
+/// Most sounds heard at once, new ones are dropped beyond it
+const MAX_VOICES: usize = 12;
+/// Contact impulse below which an impact makes no sound
+const MIN_IMPULSE: f32 = 0.2;
+/// Contact impulse from which an impact is as loud as it gets
+const LOUD_IMPULSE: f32 = 10.0;
+/// Random pitch variation, so repeated sounds don't feel canned
+const PITCH_JITTER: f32 = 0.05;
+/// Speed of a dice on the table from which its rolling is as loud as it gets
+const LOUD_ROLLING_SPEED: f32 = 5.0;
+const ROLLING_VOLUME: f32 = 0.5;
+
+#[derive(Resource)]
+struct SfxAssets {
+    dice_on_tray: Handle<AudioSource>,
+    dice_on_ring: Handle<AudioSource>,
+    dice_on_dice: Handle<AudioSource>,
+    throw: Handle<AudioSource>,
+    pickup: Handle<AudioSource>,
+    rolling: Handle<AudioSource>,
+}
+
+impl FromWorld for SfxAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        Self {
+            dice_on_tray: asset_server.load(asset_path("sfx/dice_tray.wav")),
+            dice_on_ring: asset_server.load(asset_path("sfx/dice_ring.wav")),
+            dice_on_dice: asset_server.load(asset_path("sfx/dice_dice.wav")),
+            throw: asset_server.load(asset_path("sfx/throw.wav")),
+            pickup: asset_server.load(asset_path("sfx/pickup.wav")),
+            rolling: asset_server.load(asset_path("sfx/dice_roll.wav")),
+        }
+    }
+}
+
+/// A sound effect being played, despawned once done
+#[derive(Component)]
+struct SfxVoice;
+
+/// Rolling sound looping under each dice, silent unless it moves on the table
+#[derive(Component)]
+struct RollingVoice;
+
+/// Every sound counting against [`MAX_VOICES`], the rolling ones only while audible
+#[derive(SystemParam)]
+struct Voices<'w, 's> {
+    q_sfx: Query<'w, 's, (), With<SfxVoice>>,
+    q_rolling: Query<'w, 's, &'static AudioSink, With<RollingVoice>>,
+}
+
+impl Voices<'_, '_> {
+    fn count(&self) -> usize {
+        self.q_sfx.iter().count()
+            + self
+                .q_rolling
+                .iter()
+                .filter(|sink| sink.volume() > 0.0)
+                .count()
+    }
+}
+
+fn play_sound(
+    commands: &mut Commands,
+    audio: &AudioSettings,
+    sound: &Handle<AudioSource>,
+    volume: f32,
+    speed: f32,
+) {
+    commands.spawn((
+        SfxVoice,
+        AudioBundle {
+            source: sound.clone(),
+            settings: PlaybackSettings::DESPAWN
+                .with_volume(Volume::new(audio.volume(AudioChannel::Sfx, volume)))
+                .with_speed(speed + thread_rng().gen_range(-PITCH_JITTER..PITCH_JITTER)),
+        },
+    ));
+}
+
+fn play_impact_sounds(
+    mut commands: Commands,
+    mut collision_started: EventReader<CollisionStarted>,
+    collisions: Res<Collisions>,
+    audio: Res<AudioSettings>,
+    sfx: Res<SfxAssets>,
+    q_collider_parents: Query<&ColliderParent>,
+    q_dices: Query<(), With<Dice>>,
+    q_table_parts: Query<&TablePart>,
+    voices: Voices,
+) {
+    let mut nb_voices = voices.count();
+
+    // Colliders are children of the dices
+    let body = |collider: Entity| {
+        q_collider_parents
+            .get(collider)
+            .map_or(collider, ColliderParent::get)
+    };
+    let surface_sound = |dice: Entity, other: Entity| {
+        if !q_dices.contains(dice) {
+            None
+        } else if q_dices.contains(other) {
+            Some(&sfx.dice_on_dice)
+        } else if let Ok(TablePart::Ring) = q_table_parts.get(other) {
+            Some(&sfx.dice_on_ring)
+        } else {
+            Some(&sfx.dice_on_tray)
+        }
+    };
+
+    for CollisionStarted(collider1, collider2) in collision_started.read() {
+        if nb_voices >= MAX_VOICES {
+            break;
+        }
+
+        let Some(contacts) = collisions.get(*collider1, *collider2) else {
+            continue;
+        };
+        if contacts.total_normal_impulse < MIN_IMPULSE {
+            continue;
+        }
+
+        let (body1, body2) = (body(*collider1), body(*collider2));
+        let Some(sound) = surface_sound(body1, body2).or_else(|| surface_sound(body2, body1))
+        else {
+            continue;
+        };
+
+        // Harder hits are louder and a bit higher pitched
+        let strength =
+            ((contacts.total_normal_impulse - MIN_IMPULSE) / (LOUD_IMPULSE - MIN_IMPULSE)).min(1.0);
+        play_sound(
+            &mut commands,
+            &audio,
+            sound,
+            0.2 + 0.8 * strength,
+            0.85 + 0.3 * strength,
+        );
+        nb_voices += 1;
+    }
+}
+
+fn spawn_rolling_voices(
+    mut commands: Commands,
+    sfx: Res<SfxAssets>,
+    q_dices: Query<Entity, Added<Dice>>,
+) {
+    for dice in &q_dices {
+        commands.entity(dice).with_children(|c| {
+            c.spawn((
+                RollingVoice,
+                AudioBundle {
+                    source: sfx.rolling.clone(),
+                    settings: PlaybackSettings::LOOP.with_volume(Volume::new(0.0)),
+                },
+            ));
+        });
+    }
+}
+
+fn update_rolling_voices(
+    collisions: Res<Collisions>,
+    audio: Res<AudioSettings>,
+    voices: Voices,
+    q_voices: Query<(&Parent, &AudioSink), With<RollingVoice>>,
+    q_dices: Query<&LinearVelocity, With<Dice>>,
+    q_children: Query<&Children>,
+    q_table_parts: Query<Entity, With<TablePart>>,
+) {
+    let mut nb_voices = voices.count();
+
+    for (parent, sink) in &q_voices {
+        let dice = parent.get();
+        let Ok(velocity) = q_dices.get(dice) else {
+            continue;
+        };
+
+        let on_table = q_children.iter_descendants(dice).any(|collider| {
+            q_table_parts
+                .iter()
+                .any(|table_part| collisions.contains(collider, table_part))
+        });
+        // A silent dice only starts rolling out loud with room for it
+        let audible = sink.volume() > 0.0;
+        let level = if on_table && (audible || nb_voices < MAX_VOICES) {
+            ROLLING_VOLUME * (velocity.0.length() / LOUD_ROLLING_SPEED).min(1.0)
+        } else {
+            0.0
+        };
+
+        let volume = audio.sink_volume(AudioChannel::Sfx, level);
+        if !audible && volume > 0.0 {
+            nb_voices += 1;
+        }
+        sink.set_volume(volume);
+    }
+}
+
+fn play_throw_sound(
+    _trigger: Trigger<RollDice>,
+    mut commands: Commands,
+    audio: Res<AudioSettings>,
+    sfx: Res<SfxAssets>,
+    voices: Voices,
+) {
+    if voices.count() < MAX_VOICES {
+        play_sound(&mut commands, &audio, &sfx.throw, 0.6, 1.0);
+    }
+}
+
+/// Taking dices in hand, for the player and the NPC
+fn play_pickup_sound<E: Event>(
+    _trigger: Trigger<E>,
+    mut commands: Commands,
+    audio: Res<AudioSettings>,
+    sfx: Res<SfxAssets>,
+    voices: Voices,
+) {
+    if voices.count() < MAX_VOICES {
+        play_sound(&mut commands, &audio, &sfx.pickup, 0.5, 1.0);
+    }
+}
+
+/// Dice impacts, rolling, throws and pickups
+pub struct SfxPlugin;
+impl Plugin for SfxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SfxAssets>()
+            .add_systems(
+                Update,
+                (
+                    play_impact_sounds,
+                    spawn_rolling_voices,
+                    update_rolling_voices,
+                ),
+            )
+            .observe(play_throw_sound)
+            .observe(play_pickup_sound::<PickupDice>)
+            .observe(play_pickup_sound::<NPCPickup>);
+    }
+}
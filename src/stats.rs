@@ -0,0 +1,447 @@
+use std::collections::{BTreeMap, HashSet};
+
+use bevy::{color::palettes::css::GOLD, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    combination::{Combination, DiceResult, Roll},
+    dice::Dice,
+    game::{GameState, Outcome, RetriesUsed},
+    storage,
+    ui::{spawn_button, spawn_menu, DisplayScore, OpenOverlay, Overlay, OVERLAY_Z_INDEX},
+};
+
+const STATS_KEY: &str = "stats";
+const STATS_FONT_SIZE: f32 = 24.0;
+/// Width of the bar of the most frequent combination, in pixels
+const BAR_WIDTH: f32 = 300.0;
+const BAR_HEIGHT: f32 = 10.0;
+const NPC_BAR_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+
+/// Totals over every round ever played, saved between sessions
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub rounds: u32,
+    pub wins: u32,
+    pub losses: u32,
+    /// Rounds ending with each kind of combination, for the player
+    pub player_combinations: BTreeMap<String, u32>,
+    pub npc_combinations: BTreeMap<String, u32>,
+    /// Dices of the best combination the player made
+    pub best_roll: Option<Vec<DiceResult>>,
+    pub win_streak: u32,
+    pub longest_win_streak: u32,
+    /// Dices picked up again by the player, over every round
+    pub retries: u32,
+    /// Dices thrown off the table, by both sides
+    pub fallen_dices: u32,
+}
+
+impl LifetimeStats {
+    fn win_rate(&self) -> Option<f32> {
+        (self.rounds > 0).then(|| self.wins as f32 / self.rounds as f32)
+    }
+
+    fn average_retries(&self) -> Option<f32> {
+        (self.rounds > 0).then(|| self.retries as f32 / self.rounds as f32)
+    }
+
+    /// The best combination the player made, with its dices
+    fn best_roll(&self) -> Option<Roll> {
+        self.best_roll.clone().map(Roll::new)
+    }
+}
+
+/// The kind of combination made the most, with how many times, the best kind on a tie
+fn most_frequent(combinations: &BTreeMap<String, u32>) -> Option<(&'static str, u32)> {
+    // The last of the maximums is kept, so the kinds go from the worst
+    Combination::KINDS
+        .iter()
+        .rev()
+        .filter_map(|kind| Some((*kind, *combinations.get(*kind)?)))
+        .max_by_key(|(_, count)| *count)
+}
+
+#[derive(Component)]
+struct CloseStatsButton;
+
+#[derive(Component)]
+struct StatsPanel;
+
+/// Filled with the stats each time the panel opens
+#[derive(Component)]
+struct StatsContent;
+
+fn record_round_stats(
+    trigger: Trigger<DisplayScore>,
+    retries_used: Res<RetriesUsed>,
+    mut stats: ResMut<LifetimeStats>,
+) {
+    let DisplayScore::Result {
+        npc,
+        player,
+        outcome,
+        ..
+    } = trigger.event()
+    else {
+        return;
+    };
+
+    stats.rounds += 1;
+    stats.retries += u32::from(retries_used.0);
+    match outcome {
+        Outcome::Win => {
+            stats.wins += 1;
+            stats.win_streak += 1;
+            stats.longest_win_streak = stats.longest_win_streak.max(stats.win_streak);
+        }
+        Outcome::Lose => {
+            stats.losses += 1;
+            stats.win_streak = 0;
+        }
+        Outcome::Push => {}
+    }
+
+    *stats
+        .player_combinations
+        .entry(player.combination.kind().into())
+        .or_default() += 1;
+    *stats
+        .npc_combinations
+        .entry(npc.combination.kind().into())
+        .or_default() += 1;
+
+    if stats
+        .best_roll()
+        .is_none_or(|best| player.combination > best.combination)
+    {
+        stats.best_roll = Some(player.results.clone());
+    }
+}
+
+/// Counts the dices going below the table during a match, once per fall
+fn count_fallen_dices(
+    state: Res<State<GameState>>,
+    mut fallen: Local<HashSet<Entity>>,
+    mut stats: ResMut<LifetimeStats>,
+    q_dices: Query<(Entity, &Transform), With<Dice>>,
+) {
+    for (entity, transform) in &q_dices {
+        if transform.translation.y >= 0.0 {
+            fallen.remove(&entity);
+        } else if fallen.insert(entity) && state.get().is_playing() {
+            stats.fallen_dices += 1;
+        }
+    }
+}
+
+fn save_stats(stats: Res<LifetimeStats>) {
+    if stats.is_changed() && !stats.is_added() {
+        storage::save(STATS_KEY, &*stats);
+    }
+}
+
+fn setup_stats_menu(mut commands: Commands) {
+    let panel = spawn_menu(&mut commands, StatsPanel, Display::None, |c| {
+        c.spawn(TextBundle::from_section(
+            "Statistics",
+            TextStyle {
+                font_size: 60.0,
+                ..default()
+            },
+        ));
+
+        c.spawn((
+            StatsContent,
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(5.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+
+        spawn_button(c, "Close", 30.0, CloseStatsButton);
+    });
+
+    commands
+        .entity(panel)
+        .insert(ZIndex::Global(OVERLAY_Z_INDEX));
+}
+
+fn spawn_stats_text(parent: &mut ChildBuilder, value: impl Into<String>, color: Color) {
+    parent.spawn(TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size: STATS_FONT_SIZE,
+            color,
+            ..default()
+        },
+    ));
+}
+
+fn fill_stats(parent: &mut ChildBuilder, stats: &LifetimeStats) {
+    let percent = |value: Option<f32>| value.map_or("-".into(), |v| format!("{:.0}%", v * 100.0));
+    let frequent = |combinations| {
+        most_frequent(combinations).map_or("-".into(), |(kind, count)| format!("{kind} ({count})"))
+    };
+
+    let lines = [
+        format!(
+            "Rounds played: {} ({} won, {} lost, {} pushed)",
+            stats.rounds,
+            stats.wins,
+            stats.losses,
+            stats.rounds.saturating_sub(stats.wins + stats.losses)
+        ),
+        format!("Win rate: {}", percent(stats.win_rate())),
+        format!("Longest win streak: {}", stats.longest_win_streak),
+        format!(
+            "Average retries used: {}",
+            stats
+                .average_retries()
+                .map_or("-".into(), |average| format!("{average:.1}"))
+        ),
+        format!(
+            "Best combination: {}",
+            stats
+                .best_roll()
+                .map_or("-".into(), |best| best.combination.to_string())
+        ),
+        format!(
+            "Most frequent: {} for you, {} for the NPC",
+            frequent(&stats.player_combinations),
+            frequent(&stats.npc_combinations)
+        ),
+        format!("Fallen dices: {}", stats.fallen_dices),
+    ];
+    for line in lines {
+        spawn_stats_text(parent, line, Color::WHITE);
+    }
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                column_gap: Val::Px(20.0),
+                margin: UiRect::top(Val::Px(15.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|c| {
+            spawn_stats_text(c, "Combinations:", Color::WHITE);
+            spawn_stats_text(c, "You", GOLD.into());
+            spawn_stats_text(c, "NPC", NPC_BAR_COLOR);
+        });
+
+    let max_count = stats
+        .player_combinations
+        .values()
+        .chain(stats.npc_combinations.values())
+        .copied()
+        .max()
+        .unwrap_or_default()
+        .max(1);
+
+    for kind in Combination::KINDS {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|c| {
+                c.spawn(TextBundle {
+                    style: Style {
+                        width: Val::Px(180.0),
+                        ..default()
+                    },
+                    text: Text::from_section(
+                        kind,
+                        TextStyle {
+                            font_size: STATS_FONT_SIZE,
+                            ..default()
+                        },
+                    ),
+                    ..default()
+                });
+
+                c.spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(2.0),
+                        width: Val::Px(BAR_WIDTH + 60.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|c| {
+                    for (combinations, color) in [
+                        (&stats.player_combinations, Color::from(GOLD)),
+                        (&stats.npc_combinations, NPC_BAR_COLOR),
+                    ] {
+                        let count = combinations.get(kind).copied().unwrap_or_default();
+                        spawn_bar(c, count, count as f32 / max_count as f32, color);
+                    }
+                });
+            });
+    }
+}
+
+/// A horizontal bar `fraction` of the full width, followed by its count
+fn spawn_bar(parent: &mut ChildBuilder, count: u32, fraction: f32, color: Color) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(5.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|c| {
+            c.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(BAR_WIDTH * fraction),
+                    height: Val::Px(BAR_HEIGHT),
+                    ..default()
+                },
+                background_color: color.into(),
+                ..default()
+            });
+
+            c.spawn(TextBundle::from_section(
+                count.to_string(),
+                TextStyle {
+                    font_size: STATS_FONT_SIZE / 2.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn open_stats(
+    trigger: Trigger<OpenOverlay>,
+    mut commands: Commands,
+    stats: Res<LifetimeStats>,
+    mut q_panel: Query<&mut Style, With<StatsPanel>>,
+    q_content: Query<Entity, With<StatsContent>>,
+) {
+    if trigger.event().0 != Overlay::Stats {
+        return;
+    }
+
+    q_panel.single_mut().display = Display::Flex;
+    commands
+        .entity(q_content.single())
+        .despawn_descendants()
+        .with_children(|c| fill_stats(c, &stats));
+}
+
+fn close_stats(
+    q_btn: Query<&Interaction, (Changed<Interaction>, With<CloseStatsButton>)>,
+    mut q_panel: Query<&mut Style, With<StatsPanel>>,
+) {
+    if q_btn.iter().any(|i| *i == Interaction::Pressed) {
+        q_panel.single_mut().display = Display::None;
+    }
+}
+
+/// Loads the lifetime statistics at startup, updates them each round and saves them
+pub struct StatsPlugin;
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        let stats = storage::load::<LifetimeStats>(STATS_KEY).unwrap_or_default();
+
+        app.insert_resource(stats)
+            .observe(record_round_stats)
+            .observe(open_stats)
+            .add_systems(Startup, setup_stats_menu)
+            .add_systems(Update, (close_stats, count_fallen_dices, save_stats));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play_round(app: &mut App, player: Vec<DiceResult>, npc: Vec<DiceResult>, outcome: Outcome) {
+        app.world_mut().trigger(DisplayScore::Result {
+            npc: Roll::new(npc),
+            player: Roll::new(player),
+            outcome,
+            payout: 0,
+            tie_break: None,
+        });
+        app.world_mut().flush();
+    }
+
+    fn stats_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<LifetimeStats>()
+            .insert_resource(RetriesUsed(1))
+            .observe(record_round_stats);
+        // Registers the observer
+        app.world_mut().flush();
+        app
+    }
+
+    #[test]
+    fn win_streak_resets_on_a_loss_only() {
+        let mut app = stats_app();
+
+        play_round(&mut app, vec![6, 5, 4], vec![3, 5, 6], Outcome::Win);
+        play_round(&mut app, vec![4, 2, 1], vec![6, 6, 6], Outcome::Win);
+        play_round(&mut app, vec![2, 3, 5], vec![2, 3, 5], Outcome::Push);
+
+        let stats = app.world().resource::<LifetimeStats>();
+        assert_eq!(stats.win_streak, 2);
+        assert_eq!(stats.longest_win_streak, 2);
+
+        play_round(&mut app, vec![2, 3, 5], vec![4, 2, 1], Outcome::Lose);
+        play_round(&mut app, vec![5, 5, 5], vec![2, 3, 5], Outcome::Win);
+
+        let stats = app.world().resource::<LifetimeStats>();
+        assert_eq!(stats.win_streak, 1);
+        assert_eq!(stats.longest_win_streak, 2);
+        assert_eq!((stats.rounds, stats.wins, stats.losses), (5, 3, 1));
+        assert_eq!(stats.retries, 5);
+    }
+
+    #[test]
+    fn best_roll_is_only_replaced_by_a_better_one() {
+        let mut app = stats_app();
+
+        play_round(&mut app, vec![3, 3, 3], vec![2, 3, 5], Outcome::Win);
+        play_round(&mut app, vec![6, 5, 4], vec![2, 3, 5], Outcome::Win);
+        assert_eq!(
+            app.world().resource::<LifetimeStats>().best_roll,
+            Some(vec![3, 3, 3])
+        );
+
+        play_round(&mut app, vec![1, 2, 4], vec![2, 3, 5], Outcome::Win);
+        assert_eq!(
+            app.world().resource::<LifetimeStats>().best_roll,
+            Some(vec![1, 2, 4])
+        );
+    }
+
+    #[test]
+    fn most_frequent_ties_go_to_the_best_kind() {
+        let combinations = BTreeMap::from([
+            ("Any".to_string(), 3),
+            ("Strike".to_string(), 3),
+            ("Straight".to_string(), 3),
+            ("Low Roll".to_string(), 1),
+        ]);
+
+        assert_eq!(most_frequent(&combinations), Some(("Strike", 3)));
+        assert_eq!(most_frequent(&BTreeMap::new()), None);
+    }
+}
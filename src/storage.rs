@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+const APP_NAME: &str = "quatredeuxun";
+
+/// Loads a value previously written with [`save`], if any
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let content = read(key)?;
+
+    match ron::from_str(&content) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!("Could not parse stored {key}: {err}");
+            None
+        }
+    }
+}
+
+/// Persists a value in the native config directory or in the browser local storage
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(content) => write(key, &content),
+        Err(err) => warn!("Could not serialize {key}: {err}"),
+    }
+}
+
+/// Writes a file for the player to keep, in the downloads folder or as a browser download.
+/// Returns where it went
+pub fn export(file_name: &str, content: &str) -> Option<String> {
+    let location = export_file(file_name, content);
+    if location.is_none() {
+        warn!("Could not export {file_name}");
+    }
+    location
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path(key: &str) -> Option<std::path::PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_NAME).join(format!("{key}.ron")))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(key: &str) -> Option<String> {
+    std::fs::read_to_string(path(key)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(key: &str, content: &str) {
+    let Some(path) = path(key) else {
+        warn!("No config directory to save {key}");
+        return;
+    };
+
+    if let Some(parent) = path.parent() {
+        if let Err(err) = std::fs::create_dir_all(parent) {
+            warn!("Could not create {}: {err}", parent.display());
+            return;
+        }
+    }
+
+    if let Err(err) = std::fs::write(&path, content) {
+        warn!("Could not write {}: {err}", path.display());
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn export_file(file_name: &str, content: &str) -> Option<String> {
+    let dir = dirs::download_dir().or_else(dirs::home_dir)?;
+    let path = dir.join(file_name);
+
+    if let Err(err) = std::fs::write(&path, content) {
+        warn!("Could not write {}: {err}", path.display());
+        return None;
+    }
+    Some(path.display().to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(key: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("{APP_NAME}.{key}"))
+        .ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(key: &str, content: &str) {
+    let Some(storage) = local_storage() else {
+        warn!("No local storage to save {key}");
+        return;
+    };
+
+    if storage
+        .set_item(&format!("{APP_NAME}.{key}"), content)
+        .is_err()
+    {
+        warn!("Could not write {key} to local storage");
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn export_file(file_name: &str, content: &str) -> Option<String> {
+    use web_sys::{js_sys, wasm_bindgen::JsCast};
+
+    // A link to the content as a data URL, clicked to download it
+    let href = format!(
+        "data:text/plain;charset=utf-8,{}",
+        String::from(js_sys::encode_uri_component(content))
+    );
+    let anchor = web_sys::window()?
+        .document()?
+        .create_element("a")
+        .ok()?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .ok()?;
+    anchor.set_href(&href);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    Some("your downloads".into())
+}
@@ -1,35 +1,64 @@
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{gltf::Gltf, prelude::*};
 
-use crate::dice::{Dice, InHand};
+use crate::{
+    dice::{Dice, InHand},
+    input::{Action, ActionState},
+    loading::GameAssets,
+};
+
+pub const TABLE_ASSET: &str = "table.glb";
 
 pub const TRAY_RADIUS: f32 = 10.0;
 pub const TRAY_THICKNESS: f32 = 0.1;
 pub const TRAY_RING_HEIGHT: f32 = 3.0;
 
 #[derive(Component)]
-pub struct TablePart;
+pub enum TablePart {
+    Tray,
+    Ring,
+}
 
 pub fn setup(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
+    gltfs: Res<Assets<Gltf>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
     // Table
-    commands.spawn((
-        RigidBody::Static,
-        ColliderConstructorHierarchy::new(ColliderConstructor::ConvexHullFromMesh),
-        SceneBundle {
-            scene: asset_server.load(GltfAssetLabel::Scene(0).from_asset("table.glb")),
-            transform: Transform::from_xyz(0.0, -10.0, 0.0).with_scale(Vec3::splat(10.0)),
-            ..default()
-        },
-    ));
+    if let Some(scene) = gltfs
+        .get(&game_assets.table)
+        .and_then(|gltf| gltf.scenes.first())
+    {
+        commands.spawn((
+            RigidBody::Static,
+            ColliderConstructorHierarchy::new(ColliderConstructor::ConvexHullFromMesh),
+            SceneBundle {
+                scene: scene.clone(),
+                transform: Transform::from_xyz(0.0, -10.0, 0.0).with_scale(Vec3::splat(10.0)),
+                ..default()
+            },
+        ));
+    } else {
+        // Plain slab below the tray, low enough for dices falling on it to count as fallen
+        let size = Vec3::new(TRAY_RADIUS * 4.0, 1.0, TRAY_RADIUS * 3.0);
+        commands.spawn((
+            Name::new("table"),
+            RigidBody::Static,
+            Collider::cuboid(size.x, size.y, size.z),
+            PbrBundle {
+                mesh: meshes.add(Cuboid::from_size(size)),
+                material: materials.add(Color::srgb(0.4, 0.25, 0.1)),
+                transform: Transform::from_xyz(0.0, -2.0 - size.y / 2.0, 0.0),
+                ..default()
+            },
+        ));
+    }
 
     // Dice tray
     commands.spawn((
-        TablePart,
+        TablePart::Tray,
         RigidBody::Static,
         ColliderConstructor::TrimeshFromMesh,
         Friction::new(0.9),
@@ -42,7 +71,7 @@ pub fn setup(
 
     // dice tray ring
     commands.spawn((
-        TablePart,
+        TablePart::Ring,
         RigidBody::Static,
         ColliderConstructor::TrimeshFromMesh,
         Friction::new(0.9),
@@ -73,13 +102,13 @@ pub fn setup(
 }
 
 pub fn punch_table(
-    button_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     collisions: Res<Collisions>,
     q_table_parts: Query<Entity, With<TablePart>>,
     mut q_dices: Query<(Entity, &mut LinearVelocity), (With<Dice>, Without<InHand>)>,
     q_children: Query<&Children>,
 ) {
-    if button_input.just_pressed(KeyCode::Space) {
+    if actions.just_pressed(Action::Punch) {
         for (entity, mut linear_velocity) in &mut q_dices {
             if q_table_parts.iter().any(|table_part| {
                 q_children
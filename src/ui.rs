@@ -1,15 +1,34 @@
-use bevy::prelude::*;
+use bevy::{
+    color::palettes::css::{GOLD, GREEN, RED},
+    prelude::*,
+};
 
 use crate::{
-    combination::Combination,
-    game::{CanSkipTurn, GameState, RetriesLeft},
+    combination::{DiceResult, Roll},
+    dice::PIPS,
+    game::{Bankroll, CanSkipTurn, GameState, MatchScore, Outcome, PauseState, RetriesLeft, Side},
+    input::{Action, ActionState},
+    loading::GameAssets,
+    menu::MenuButton,
+    reveal::{Reveal, RevealedCombination, RevealedPayout},
+    settings::GameplaySettings,
 };
 
-const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+pub const FONT_ASSET: &str = "JqkasWild.ttf";
+pub const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+pub const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const SCORE_FONT_SIZE: f32 = 36.0;
+/// Above the menus opening the overlays, under the settings
+pub const OVERLAY_Z_INDEX: i32 = 5;
 
-#[derive(Component)]
-struct ScoreText;
+/// Where the rolls are shown
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum ScorePanel {
+    /// Top of the screen, while the round is played
+    Round,
+    /// Resolution panel, once both sides are done
+    Resolution,
+}
 
 #[derive(Component)]
 struct SkipTurnButton;
@@ -17,6 +36,35 @@ struct SkipTurnButton;
 #[derive(Component)]
 struct RetriesLeftText;
 
+#[derive(Component)]
+struct BankrollText;
+
+/// Outcome of the round, shown until the player continues
+#[derive(Component)]
+struct ResolutionPanel;
+
+#[derive(Component)]
+pub struct OutcomeTitle;
+
+#[derive(Component)]
+struct OutcomeText;
+
+#[derive(Component)]
+struct ContinueButton;
+
+/// Rounds or points of each side in the current match
+#[derive(Component)]
+struct ScoreboardText;
+
+#[derive(Component)]
+struct MatchOverPanel;
+
+#[derive(Component)]
+struct MatchOverTitle;
+
+#[derive(Component)]
+struct RematchButton;
+
 fn setup_ui(mut commands: Commands) {
     commands.observe(on_display_score);
 
@@ -32,109 +80,599 @@ fn setup_ui(mut commands: Commands) {
         })
         .with_children(|c| {
             c.spawn((
-                ScoreText,
-                TextBundle::from_section(
-                    "",
-                    TextStyle {
-                        font_size: 50.0,
-                        ..default()
-                    },
-                ),
-            ));
-
-            c.spawn((
-                SkipTurnButton,
-                ButtonBundle {
+                ScorePanel::Round,
+                NodeBundle {
                     style: Style {
+                        flex_direction: FlexDirection::Column,
+                        flex_shrink: 1.0,
                         padding: UiRect::all(Val::Px(5.0)),
-                        height: Val::Px(50.0),
-                        border: UiRect::all(Val::Px(2.0)),
                         ..default()
                     },
-                    border_color: BorderColor(Color::WHITE),
-                    background_color: NORMAL_BUTTON.into(),
                     ..default()
                 },
-            ))
+            ));
+
+            spawn_button(c, "Stop there", 40.0, SkipTurnButton);
+
+            c.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::End,
+                    ..default()
+                },
+                ..default()
+            })
             .with_children(|c| {
-                c.spawn(TextBundle::from_section(
-                    "Stop there",
-                    TextStyle {
-                        font_size: 40.0,
-                        ..default()
-                    },
+                c.spawn((
+                    RetriesLeftText,
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 50.0,
+                            ..default()
+                        },
+                    ),
+                ));
+
+                c.spawn((
+                    BankrollText,
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 40.0,
+                            ..default()
+                        },
+                    ),
                 ));
             });
+        });
+
+    spawn_menu(&mut commands, ResolutionPanel, Display::None, |c| {
+        c.spawn((
+            OutcomeTitle,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 60.0,
+                    ..default()
+                },
+            ),
+        ));
+
+        c.spawn((
+            ScorePanel::Resolution,
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(10.0),
+                    max_width: Val::Vw(90.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+
+        c.spawn((
+            RevealedCombination,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 70.0,
+                    ..default()
+                },
+            ),
+        ));
+
+        c.spawn((
+            OutcomeText,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 40.0,
+                    ..default()
+                },
+            )
+            .with_text_justify(JustifyText::Center),
+        ));
+
+        c.spawn((
+            RevealedPayout,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 50.0,
+                    ..default()
+                },
+            ),
+        ));
+
+        spawn_button(c, "Continue", 40.0, ContinueButton);
+    });
 
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+            ..default()
+        })
+        .with_children(|c| {
             c.spawn((
-                RetriesLeftText,
+                ScoreboardText,
                 TextBundle::from_section(
                     "",
                     TextStyle {
-                        font_size: 50.0,
+                        font_size: 30.0,
                         ..default()
                     },
                 ),
             ));
         });
+
+    spawn_menu(&mut commands, MatchOverPanel, Display::None, |c| {
+        c.spawn((
+            MatchOverTitle,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 60.0,
+                    ..default()
+                },
+            ),
+        ));
+
+        spawn_button(c, "Rematch", 40.0, RematchButton);
+        spawn_button(c, "Main menu", 40.0, MenuButton::MainMenu);
+    });
 }
 
-#[derive(Event)]
-pub struct DisplayScore {
-    npc: Combination,
-    player: Option<(Combination, bool)>, // (combination, wins) if player threw dices
+/// Spawns a bordered text button carrying `bundle`
+pub fn spawn_button(
+    parent: &mut ChildBuilder,
+    label: &str,
+    font_size: f32,
+    bundle: impl Bundle,
+) -> Entity {
+    parent
+        .spawn((
+            bundle,
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(5.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                border_color: BorderColor(Color::WHITE),
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+        ))
+        .with_children(|c| {
+            c.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size,
+                    ..default()
+                },
+            ));
+        })
+        .id()
 }
 
-impl DisplayScore {
-    pub fn npc(npc: Combination) -> Self {
-        Self { npc, player: None }
+/// Spawns a centered panel over the game, with `bundle` on its full screen root
+pub fn spawn_menu(
+    commands: &mut Commands,
+    bundle: impl Bundle,
+    display: Display,
+    children: impl FnOnce(&mut ChildBuilder),
+) -> Entity {
+    commands
+        .spawn((
+            bundle,
+            NodeBundle {
+                style: Style {
+                    display,
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|c| {
+            c.spawn((
+                // Catches clicks so they don't reach the table
+                Interaction::default(),
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(10.0),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+                    ..default()
+                },
+            ))
+            .with_children(children);
+        })
+        .id()
+}
+
+/// Panels opened over other menus
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Overlay {
+    Settings,
+    History,
+    Stats,
+}
+
+impl Overlay {
+    fn label(self) -> &'static str {
+        match self {
+            Overlay::Settings => "Settings",
+            Overlay::History => "History",
+            Overlay::Stats => "Statistics",
+        }
     }
+}
+
+#[derive(Component)]
+struct OverlayButton(Overlay);
+
+/// Triggered by the buttons opening an overlay, for its plugin to show it
+#[derive(Event)]
+pub struct OpenOverlay(pub Overlay);
+
+/// Spawns a button opening `overlay`, for other menus
+pub fn spawn_overlay_button(parent: &mut ChildBuilder, overlay: Overlay, font_size: f32) -> Entity {
+    spawn_button(parent, overlay.label(), font_size, OverlayButton(overlay))
+}
+
+#[derive(Event)]
+pub enum DisplayScore {
+    /// The leader is done, the other side has to beat this
+    ToBeat { side: Side, roll: Roll },
+    /// Both sides tied, a single dice each decides
+    SuddenDeath,
+    /// Both sides are done
+    Result {
+        npc: Roll,
+        player: Roll,
+        outcome: Outcome,
+        /// Money won or lost by the player
+        payout: i32,
+        /// Player and NPC dices of the sudden death, if there was one
+        tie_break: Option<(DiceResult, DiceResult)>,
+    },
+}
 
-    pub fn player(npc: Combination, player: Combination, wins: bool) -> Self {
-        Self {
+fn on_display_score(
+    trigger: Trigger<DisplayScore>,
+    mut commands: Commands,
+    q_panels: Query<(Entity, &ScorePanel)>,
+    mut q_outcome_title: Query<&mut Text, With<OutcomeTitle>>,
+    mut q_outcome: Query<&mut Text, (With<OutcomeText>, Without<OutcomeTitle>)>,
+) {
+    let panel = |kind: ScorePanel| {
+        q_panels
+            .iter()
+            .find(|(_, panel)| **panel == kind)
+            .map(|(entity, _)| entity)
+            .unwrap()
+    };
+
+    let mut round_panel = commands.entity(panel(ScorePanel::Round));
+    round_panel.despawn_descendants();
+
+    match trigger.event() {
+        DisplayScore::ToBeat { side, roll } => {
+            round_panel.with_children(|c| {
+                spawn_score_text(c, "To beat:", Color::WHITE);
+                spawn_score_row(c, *side, roll, None);
+            });
+        }
+        DisplayScore::SuddenDeath => {
+            round_panel.with_children(|c| {
+                spawn_score_text(c, "Tie! Sudden death, one dice each", Color::WHITE);
+            });
+        }
+        DisplayScore::Result {
             npc,
-            player: Some((player, wins)),
+            player,
+            outcome,
+            tie_break,
+            ..
+        } => {
+            q_outcome_title.single_mut().sections[0].value = match outcome {
+                Outcome::Win => "You win!",
+                Outcome::Lose => "You lose!",
+                Outcome::Push => "Push",
+            }
+            .into();
+
+            commands
+                .entity(panel(ScorePanel::Resolution))
+                .despawn_descendants()
+                .with_children(|c| {
+                    spawn_score_row(c, Side::Npc, npc, Some(outcome.opposite()));
+                    spawn_score_row(c, Side::Player, player, Some(*outcome));
+                });
+
+            q_outcome.single_mut().sections[0].value = match tie_break {
+                Some((player_dice, npc_dice)) => {
+                    format!("Sudden death: you {player_dice}, NPC {npc_dice}")
+                }
+                None => String::new(),
+            };
         }
     }
 }
 
-fn on_display_score(trigger: Trigger<DisplayScore>, mut query: Query<&mut Text, With<ScoreText>>) {
-    let score = trigger.event();
-    let mut text = query.single_mut();
+fn spawn_score_text(parent: &mut ChildBuilder, value: impl Into<String>, color: Color) {
+    parent.spawn(TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size: SCORE_FONT_SIZE,
+            color,
+            ..default()
+        },
+    ));
+}
 
-    text.sections[0].value = match &score.player {
-        Some((player, wins)) => format!(
-            "To beat: {}.\nYou scored: {}\n{}",
-            score.npc,
-            player,
-            if *wins { "You win!" } else { "You lose!" }
-        ),
-        None => format!("To beat: {}", score.npc),
+/// A side with its dices, combination, value and outcome, wrapping on narrow screens
+fn spawn_score_row(parent: &mut ChildBuilder, side: Side, roll: &Roll, outcome: Option<Outcome>) {
+    let contributors = roll.combination.contributors(&roll.results);
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_wrap: FlexWrap::Wrap,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(15.0),
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|c| {
+            c.spawn(NodeBundle {
+                style: Style {
+                    min_width: Val::Px(SCORE_FONT_SIZE * 2.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|c| {
+                let name = match side {
+                    Side::Player => "You",
+                    Side::Npc => "NPC",
+                };
+                spawn_score_text(c, name, Color::WHITE);
+            });
+
+            c.spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(5.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|c| {
+                for (i, result) in roll.results.iter().enumerate() {
+                    spawn_dice_icon(c, *result, contributors.contains(&i));
+                }
+            });
+
+            spawn_score_text(c, roll.combination.name(), Color::WHITE);
+            spawn_score_text(c, format!("{}¤", roll.combination.score()), GOLD.into());
+
+            if let Some(outcome) = outcome {
+                spawn_badge(c, outcome);
+            }
+        });
+}
+
+/// The face of a dice with its pips, outlined when it makes the combination
+fn spawn_dice_icon(parent: &mut ChildBuilder, result: DiceResult, contributing: bool) {
+    let size = Val::VMin(5.0);
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: size,
+                height: size,
+                min_width: Val::Px(24.0),
+                min_height: Val::Px(24.0),
+                display: Display::Grid,
+                grid_template_columns: RepeatedGridTrack::flex(3, 1.0),
+                grid_template_rows: RepeatedGridTrack::flex(3, 1.0),
+                padding: UiRect::all(Val::Percent(8.0)),
+                border: UiRect::all(Val::Px(3.0)),
+                ..default()
+            },
+            background_color: Color::WHITE.into(),
+            border_color: BorderColor(if contributing {
+                GOLD.into()
+            } else {
+                Color::WHITE
+            }),
+            border_radius: BorderRadius::all(Val::Percent(15.0)),
+            ..default()
+        })
+        .with_children(|c| {
+            let Some(pips) = PIPS.get(usize::from(result).wrapping_sub(1)) else {
+                // No pips for this value, only the number
+                c.spawn(TextBundle {
+                    style: Style {
+                        grid_column: GridPlacement::start(2),
+                        grid_row: GridPlacement::start(2),
+                        ..default()
+                    },
+                    text: Text::from_section(
+                        result.to_string(),
+                        TextStyle {
+                            font_size: SCORE_FONT_SIZE / 2.0,
+                            color: Color::BLACK,
+                            ..default()
+                        },
+                    ),
+                    ..default()
+                });
+                return;
+            };
+
+            for (x, y) in *pips {
+                c.spawn(NodeBundle {
+                    style: Style {
+                        grid_column: GridPlacement::start(*x as i16 + 2),
+                        grid_row: GridPlacement::start(*y as i16 + 2),
+                        margin: UiRect::all(Val::Percent(15.0)),
+                        ..default()
+                    },
+                    background_color: Color::BLACK.into(),
+                    border_radius: BorderRadius::MAX,
+                    ..default()
+                });
+            }
+        });
+}
+
+fn spawn_badge(parent: &mut ChildBuilder, outcome: Outcome) {
+    let (label, color) = match outcome {
+        Outcome::Win => ("WIN", GREEN.into()),
+        Outcome::Lose => ("LOSE", RED.into()),
+        Outcome::Push => ("PUSH", NORMAL_BUTTON),
+    };
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                padding: UiRect::axes(Val::Px(10.0), Val::Px(2.0)),
+                ..default()
+            },
+            background_color: BackgroundColor(color),
+            border_radius: BorderRadius::all(Val::Px(5.0)),
+            ..default()
+        })
+        .with_children(|c| {
+            spawn_score_text(c, label, Color::WHITE);
+        });
+}
+
+fn update_overlay_buttons(
+    mut commands: Commands,
+    q_btn: Query<(&Interaction, &OverlayButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in &q_btn {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(OpenOverlay(button.0));
+        }
+    }
+}
+
+fn update_button_colors(
+    mut q_btn: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
+) {
+    for (interaction, mut color) in &mut q_btn {
+        *color = match *interaction {
+            Interaction::Pressed | Interaction::Hovered => HOVERED_BUTTON.into(),
+            Interaction::None => NORMAL_BUTTON.into(),
+        };
     }
 }
 
 fn update_skip_turn_button(
-    mut q_btn: Query<
-        (&Interaction, &mut BackgroundColor),
-        (Changed<Interaction>, With<SkipTurnButton>),
-    >,
+    q_btn: Query<&Interaction, (Changed<Interaction>, With<SkipTurnButton>)>,
+    actions: Res<ActionState>,
     mut retries: ResMut<RetriesLeft>,
 ) {
-    for (interaction, mut color) in &mut q_btn {
-        match *interaction {
-            Interaction::Pressed => {
-                retries.0 = 0;
-            }
-            Interaction::Hovered => {
-                *color = HOVERED_BUTTON.into();
-            }
-            Interaction::None => {
-                *color = NORMAL_BUTTON.into();
-            }
+    if actions.just_pressed(Action::SkipTurn) || q_btn.iter().any(|i| *i == Interaction::Pressed) {
+        retries.0 = 0;
+    }
+}
+
+fn update_continue_button(
+    q_btn: Query<&Interaction, (Changed<Interaction>, With<ContinueButton>)>,
+    actions: Res<ActionState>,
+    gameplay: Res<GameplaySettings>,
+    match_score: Res<MatchScore>,
+    bankroll: Res<Bankroll>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(Action::SkipTurn) || q_btn.iter().any(|i| *i == Interaction::Pressed) {
+        if bankroll.0 <= 0 {
+            next_state.set(GameState::GameOver);
+        } else if match_score.winner(gameplay.match_format).is_some() {
+            next_state.set(GameState::MatchOver);
+        } else {
+            next_state.set(GameState::RoundStart);
         }
     }
 }
 
+fn update_rematch_button(
+    q_btn: Query<&Interaction, (Changed<Interaction>, With<RematchButton>)>,
+    actions: Res<ActionState>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(Action::SkipTurn) || q_btn.iter().any(|i| *i == Interaction::Pressed) {
+        next_state.set(GameState::RoundStart);
+    }
+}
+
+fn update_scoreboard(
+    mut query: Query<&mut Text, With<ScoreboardText>>,
+    gameplay: Res<GameplaySettings>,
+    match_score: Res<MatchScore>,
+) {
+    if gameplay.is_changed() || match_score.is_changed() {
+        let (player, npc) = match_score.scores(gameplay.match_format);
+        query.single_mut().sections[0].value = format!(
+            "{}\nYou {player} - {npc} NPC",
+            gameplay.match_format.label()
+        );
+    }
+}
+
+fn show_match_over_panel(
+    mut q_panel: Query<&mut Style, With<MatchOverPanel>>,
+    mut q_title: Query<&mut Text, With<MatchOverTitle>>,
+    gameplay: Res<GameplaySettings>,
+    match_score: Res<MatchScore>,
+) {
+    let (player, npc) = match_score.scores(gameplay.match_format);
+    q_title.single_mut().sections[0].value = match match_score.winner(gameplay.match_format) {
+        Some(Side::Npc) => format!("NPC wins the match {npc} - {player}"),
+        _ => format!("You win the match {player} - {npc}"),
+    };
+
+    q_panel.single_mut().display = Display::Flex;
+}
+
+fn hide_match_over_panel(mut q_panel: Query<&mut Style, With<MatchOverPanel>>) {
+    q_panel.single_mut().display = Display::None;
+}
+
+fn show_resolution_panel(mut q_panel: Query<&mut Style, With<ResolutionPanel>>) {
+    q_panel.single_mut().display = Display::Flex;
+}
+
+fn hide_resolution_panel(mut q_panel: Query<&mut Style, With<ResolutionPanel>>) {
+    q_panel.single_mut().display = Display::None;
+}
+
 fn update_retries(mut query: Query<&mut Text, With<RetriesLeftText>>, retries: Res<RetriesLeft>) {
     if retries.is_added() || retries.is_changed() {
         let mut text = query.single_mut();
@@ -142,10 +680,31 @@ fn update_retries(mut query: Query<&mut Text, With<RetriesLeftText>>, retries: R
     }
 }
 
-pub fn apply_font(asset_server: Res<AssetServer>, mut query: Query<&mut Text, Added<Text>>) {
+fn update_bankroll(mut query: Query<&mut Text, With<BankrollText>>, bankroll: Res<Bankroll>) {
+    if bankroll.is_changed() {
+        let mut text = query.single_mut();
+        text.sections[0].value = format!("Bankroll: {}¤", bankroll.0);
+    }
+}
+
+/// Uses the game font once it is loaded, keeping Bevy's default one if it is missing
+pub fn apply_font(
+    mut font_loaded: Local<bool>,
+    game_assets: Res<GameAssets>,
+    fonts: Res<Assets<Font>>,
+    mut query: Query<&mut Text>,
+) {
+    if !fonts.contains(&game_assets.font) {
+        return;
+    }
+    let just_loaded = !*font_loaded;
+    *font_loaded = true;
+
     for mut text in &mut query {
-        for section in &mut text.sections {
-            section.style.font = asset_server.load("JqkasWild.ttf");
+        if just_loaded || text.is_added() {
+            for section in &mut text.sections {
+                section.style.font = game_assets.font.clone();
+            }
         }
     }
 }
@@ -153,15 +712,31 @@ pub fn apply_font(asset_server: Res<AssetServer>, mut query: Query<&mut Text, Ad
 pub struct UiPlugin;
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_ui).add_systems(
-            Update,
-            (
-                apply_font,
-                update_retries,
-                update_skip_turn_button
-                    .run_if(in_state(GameState::PlayerRolling))
-                    .run_if(|can: Res<CanSkipTurn>| can.0),
-            ),
-        );
+        app.add_systems(Startup, setup_ui)
+            .add_systems(
+                Update,
+                (
+                    apply_font,
+                    update_button_colors,
+                    update_overlay_buttons,
+                    update_retries,
+                    update_bankroll,
+                    (
+                        update_skip_turn_button
+                            .run_if(in_state(GameState::PlayerRolling))
+                            .run_if(|can: Res<CanSkipTurn>| can.0),
+                        update_continue_button
+                            .run_if(in_state(GameState::Resolution))
+                            .run_if(not(resource_exists::<Reveal>)),
+                    )
+                        .run_if(in_state(PauseState::Running)),
+                    update_rematch_button.run_if(in_state(GameState::MatchOver)),
+                    update_scoreboard,
+                ),
+            )
+            .add_systems(OnEnter(GameState::MatchOver), show_match_over_panel)
+            .add_systems(OnExit(GameState::MatchOver), hide_match_over_panel)
+            .add_systems(OnEnter(GameState::Resolution), show_resolution_panel)
+            .add_systems(OnExit(GameState::Resolution), hide_resolution_panel);
     }
 }